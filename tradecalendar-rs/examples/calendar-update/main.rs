@@ -1,12 +1,13 @@
 // #![feature(try_blocks)]
 
 mod calendar_helper;
+mod notice;
 
 use anyhow::{Result, anyhow};
 use clap::{Command, arg, value_parser};
 use encoding_rs_io::DecodeReaderBytes;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::PathBuf;
 
 #[cfg(feature = "with-chrono")]
@@ -15,8 +16,10 @@ use chrono::NaiveDate;
 use jiff::civil::Date;
 
 use calendar_helper::*;
+use notice::*;
+use tradecalendar::{holidays_to_tradingdays, jcswitch::MyDateType, tradingdays_to_calendar};
 
-// input 节假日文件格式如下
+// input 节假日文件格式如下, 可以有第3列_kind(holiday或者makeup), makeup表示调休上班日, 将被忽略
 /*
 date,name
 2023-01-02,元旦
@@ -39,7 +42,7 @@ date,name
 2023-10-06,国庆
 */
 
-/// 输入节假日文件(不含周六周日)，生成交易日文件和calendar文件
+/// 输入节假日文件(不含周六周日)或者国务院办公厅的放假通知，生成交易日文件和calendar文件
 fn main() -> Result<()> {
     let matches = Command::new("交易日更新程序")
         .version("0.1.0")
@@ -47,21 +50,48 @@ fn main() -> Result<()> {
         .about("Convert Holidays to Tradingdays, generate postgres/clickhouse sql files")
         .arg(
             arg!(-i --input <FILE> "holidays输入文件完整路径,格式为每行一条节假日%Y-%m-%d,或者逗号分隔取第一条,不含周六周日,没有csv header行")
-            .value_parser(value_parser!(PathBuf)))
+            .value_parser(value_parser!(PathBuf))
+            .required(false))
+        .arg(
+            arg!(-n --notice <FILE> "国务院办公厅放假通知的文本文件(可选), 解析后生成holidays.csv, 并代替-i的输入")
+            .value_parser(value_parser!(PathBuf))
+            .required(false))
+        .arg(
+            arg!(-y --year <YEAR> "放假通知对应的年份(可选), 默认从通知标题中获取")
+            .value_parser(value_parser!(i32))
+            .required(false))
         .arg(
             arg!(-o --outdir <DIR> "sql文件输出的目录(可选), 将生成pg_trade_day.sql和ch_calendar.sql")
             .required(false))
         .get_matches();
 
-    let holidays_file = matches
-        .get_one::<PathBuf>("input")
-        .ok_or_else(|| anyhow!("请使用-i指定输入的节假日文件"))?;
-
     // 如果没有提供输出目录，则在当前目录下的output目录
     let out_dir = match matches.get_one::<String>("outdir") {
         Some(output) => output,
         _ => "./output",
     };
+
+    if let Some(notice_file) = matches.get_one::<PathBuf>("notice") {
+        println!("notice file: {}", notice_file.display());
+        let mut text = String::new();
+        DecodeReaderBytes::new(File::open(notice_file)?).read_to_string(&mut text)?;
+        let notice = parse_holiday_notice(&text, matches.get_one::<i32>("year").copied())?;
+        println!("{}年节假日安排:", notice.year);
+        for holiday in notice.holidays.iter() {
+            println!("{},{}", holiday.date, holiday.name);
+        }
+        for (date, name) in notice.makeup_days.iter() {
+            println!("{} 调休上班({})", date, name);
+        }
+        gen_holidays_csv(&notice, out_dir)?;
+        let holidays: Vec<MyDateType> = notice.holidays.iter().map(|h| h.date).collect();
+        let holiday_names: Vec<String> = notice.holidays.into_iter().map(|h| h.name).collect();
+        return gen_all(out_dir, &holidays, &holiday_names);
+    }
+
+    let holidays_file = matches
+        .get_one::<PathBuf>("input")
+        .ok_or_else(|| anyhow!("请使用-i指定输入的节假日文件, 或者使用-n指定放假通知"))?;
     let current_dir = std::env::current_dir()?;
     println!(
         "当前目录: {}\ninput file: {}",
@@ -89,11 +119,13 @@ fn main() -> Result<()> {
                     let date = NaiveDate::parse_from_str(item, "%Y-%m-%d")?;
                     #[cfg(feature = "with-jiff")]
                     let date = Date::strptime("%Y-%m-%d", item)?;
-                    holidays.push(date);
-                    match splt.next() {
-                        Some(name) => holiday_names.push(name.into()),
-                        None => holiday_names.push("".to_owned()),
+                    let name = splt.next().unwrap_or("").trim();
+                    // 扩展格式的第3列为makeup时, 是调休上班日, 不是节假日
+                    if splt.next().is_some_and(|kind| kind.trim() == "makeup") {
+                        continue;
                     }
+                    holidays.push(date);
+                    holiday_names.push(name.to_owned());
                 }
             }
             None => {}
@@ -117,10 +149,15 @@ fn main() -> Result<()> {
     //     .map(|x| try { NaiveDate::parse_from_str(&(x?).split(',').next().unwrap(), "%Y-%m-%d")? })
     //     .collect::<Result<Vec<_>, anyhow::Error>>()?;
 
-    gen_holiday_sql(out_dir, &holidays, &holiday_names)?;
-    let td_list = holidays_to_tradingdays(&holidays);
+    gen_all(out_dir, &holidays, &holiday_names)
+}
+
+/// 由节假日列表生成所有输出文件
+fn gen_all(out_dir: &str, holidays: &[MyDateType], holiday_names: &[String]) -> Result<()> {
+    gen_holiday_sql(out_dir, holidays, holiday_names)?;
+    let td_list = holidays_to_tradingdays(holidays)?;
     gen_trade_day_csv(&td_list, out_dir)?;
-    let all_days = tradingdays_to_calendar(&td_list);
+    let all_days = tradingdays_to_calendar(&td_list)?;
     gen_calendar_csv(&all_days, out_dir)?;
     println!("Finished.");
 
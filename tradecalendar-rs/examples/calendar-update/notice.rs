@@ -0,0 +1,314 @@
+use anyhow::{Context, Result, anyhow};
+use std::{fs::File, path::Path};
+
+use jcswitch::*;
+use tradecalendar::*;
+
+// 国务院办公厅放假通知的格式如下
+/*
+国务院办公厅关于2026年部分节假日安排的通知
+一、元旦：1月1日（周四）至3日（周六）放假调休，共3天。1月4日（周日）上班。
+二、春节：2月15日（农历腊月二十八、周日）至23日（农历正月初七、周一）放假调休，共9天。2月14日（周六）、2月28日（周六）上班。
+三、清明节：4月4日（周六）至6日（周一）放假，共3天。
+...
+七、国庆节：10月1日（周四）至7日（周三）放假调休，共7天。9月20日（周日）、10月10日（周六）上班。
+*/
+
+/// 从放假通知中解析出的节假日安排
+#[derive(Debug, Clone, Default)]
+pub struct HolidayNotice {
+    /// 通知对应的年份
+    pub year: i32,
+    /// 放假的日期(不含周六周日), 按日期排序
+    pub holidays: Vec<Holiday>,
+    /// 调休上班的日期, 按日期排序
+    pub makeup_days: Vec<(MyDateType, String)>,
+}
+
+/// 句子中的日期及"至"
+enum Token {
+    Date(MyDateType),
+    To,
+}
+
+/// 解析国务院办公厅放假通知的文本
+///
+/// year: 通知对应的年份, None则从标题"关于xxxx年部分节假日安排的通知"中获取
+pub fn parse_holiday_notice(text: &str, year: Option<i32>) -> Result<HolidayNotice> {
+    let year = match year {
+        Some(year) => year,
+        None => notice_year(text).ok_or_else(|| anyhow!("通知中没有找到年份, 请指定年份"))?,
+    };
+    let mut notice = HolidayNotice {
+        year,
+        ..Default::default()
+    };
+
+    for line in text.lines() {
+        let line = line.trim();
+        let Some((head, body)) = line.split_once(['：', ':']) else {
+            continue;
+        };
+        // 形如"一、元旦", 去掉序号
+        let head = match head.split_once('、') {
+            Some((ordinal, rest)) if ordinal.chars().all(is_chinese_numeral) => rest,
+            _ => head,
+        };
+        if !body.contains("放假") {
+            continue;
+        }
+        let names: Vec<&str> = head.split('、').map(short_name).collect();
+
+        for sentence in remove_parentheses(body).split(['。', '；', ';']) {
+            if sentence.contains("放假") {
+                for date in expand_dates(sentence, year)? {
+                    if is_weekend(&date) {
+                        continue;
+                    }
+                    // 如元旦从上年12月31日开始放假, 这一天属于上一年度的日历
+                    if date_ymd(&date).0 != year {
+                        println!("忽略不属于{}年的假期: {}", year, date);
+                        continue;
+                    }
+                    notice.holidays.push(Holiday {
+                        date,
+                        name: holiday_name(&names, &date, year),
+                    });
+                }
+            } else if sentence.contains("上班") {
+                for date in expand_dates(sentence, year)? {
+                    notice.makeup_days.push((date, names[0].to_string()));
+                }
+            }
+        }
+    }
+    if notice.holidays.is_empty() {
+        return Err(anyhow!("通知中没有找到放假安排"));
+    }
+    notice.holidays.sort_by_key(|h| h.date);
+    notice.holidays.dedup_by_key(|h| h.date);
+    notice.makeup_days.sort();
+    notice.makeup_days.dedup_by_key(|(date, _)| *date);
+    Ok(notice)
+}
+
+/// 标题中"关于2026年"的年份
+fn notice_year(text: &str) -> Option<i32> {
+    let idx = text.find("关于")?;
+    let rest = &text[idx + "关于".len()..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if rest[digits.len()..].starts_with('年') {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_chinese_numeral(c: char) -> bool {
+    "〇一二三四五六七八九十".contains(c)
+}
+
+/// 与holidays.csv一致的简称, 如"清明节" => "清明"
+fn short_name(name: &str) -> &str {
+    let name = name.trim();
+    match name {
+        "春节" | "劳动节" => name,
+        _ => name.strip_suffix('节').unwrap_or(name),
+    }
+}
+
+/// 春节的第一天如果是除夕则称为"除夕", 国庆中秋合并放假时, 中秋当天称为"中秋"
+fn holiday_name(names: &[&str], date: &MyDateType, year: i32) -> String {
+    if names.contains(&"春节")
+        && lunar_to_solar(year, 1, 1, false).is_ok_and(|d| tomorrow(date) == d)
+    {
+        return "除夕".to_string();
+    }
+    if names.contains(&"中秋") && lunar_to_solar(year, 8, 15, false).is_ok_and(|d| &d == date) {
+        return "中秋".to_string();
+    }
+    let name = names
+        .iter()
+        .find(|name| **name != "中秋")
+        .unwrap_or(&names[0]);
+    name.to_string()
+}
+
+/// 去掉全角及半角括号中的内容, 如"（周四）", "（农历腊月二十八、周日）"
+fn remove_parentheses(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '（' | '(' => depth += 1,
+            '）' | ')' => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// 解析句子中的日期, "1月1日至3日"展开为1日、2日、3日; 省略的年份、月份取前一个日期的
+fn expand_dates(sentence: &str, year: i32) -> Result<Vec<MyDateType>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let (mut year, mut month) = (year, 0);
+    let mut number = String::new();
+    for c in sentence.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        match c {
+            '年' if !number.is_empty() => year = number.parse()?,
+            '月' if !number.is_empty() => month = number.parse()?,
+            '日' if !number.is_empty() => {
+                let day: u32 = number.parse()?;
+                if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                    return Err(anyhow!("无法识别的日期: {}", sentence));
+                }
+                tokens.push(Token::Date(make_date(year, month, day)));
+            }
+            '至' => tokens.push(Token::To),
+            _ => {}
+        }
+        number.clear();
+    }
+
+    let mut dates: Vec<MyDateType> = Vec::new();
+    let mut range = false;
+    for token in tokens {
+        match token {
+            Token::To => range = true,
+            Token::Date(date) => {
+                if range {
+                    let mut the_day = *dates
+                        .last()
+                        .ok_or_else(|| anyhow!("\"至\"前面没有日期: {}", sentence))?;
+                    while the_day < date {
+                        the_day = tomorrow(&the_day);
+                        dates.push(the_day);
+                    }
+                    range = false;
+                } else {
+                    dates.push(date);
+                }
+            }
+        }
+    }
+    Ok(dates)
+}
+
+/// 生成holidays.csv文件, 格式与顶层目录下的holidays.csv一致, 包含调休上班日
+pub fn gen_holidays_csv<P: AsRef<Path>>(notice: &HolidayNotice, out_dir: P) -> Result<()> {
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir).with_context(|| out_dir.display().to_string())?;
+    let p1 = out_dir.join("holidays.csv");
+    let mut f1 = File::create(&p1).with_context(|| p1.display().to_string())?;
+    let mut table = HolidayTable::new();
+    for holiday in notice.holidays.iter() {
+        table.add_holiday(holiday.date, &holiday.name);
+    }
+    for (date, name) in notice.makeup_days.iter() {
+        table.add_makeup_day(*date, name);
+    }
+    table.write_csv(&mut f1)?;
+    println!("holidays.csv: {}", std::fs::canonicalize(p1)?.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    const NOTICE_2026: &str = "国务院办公厅关于2026年部分节假日安排的通知
+一、元旦：1月1日（周四）至3日（周六）放假调休，共3天。1月4日（周日）上班。
+二、春节：2月15日（农历腊月二十八、周日）至23日（农历正月初七、周一）放假调休，共9天。2月14日（周六）、2月28日（周六）上班。
+三、清明节：4月4日（周六）至6日（周一）放假，共3天。
+四、劳动节：5月1日（周五）至5日（周二）放假调休，共5天。5月9日（周六）上班。
+五、端午节：6月19日（周五）至21日（周日）放假，共3天。
+六、中秋节：9月25日（周五）至27日（周日）放假，共3天。
+七、国庆节：10月1日（周四）至7日（周三）放假调休，共7天。9月20日（周日）、10月10日（周六）上班。
+";
+
+    const NOTICE_2025: &str = "国务院办公厅关于2025年部分节假日安排的通知
+一、元旦：1月1日（周三）放假1天，不调休。
+二、春节：1月28日（农历除夕、周二）至2月4日（农历正月初七、周二）放假调休，共8天。1月26日（周日）、2月8日（周六）上班。
+三、清明节：4月4日（周五）至6日（周日）放假，共3天。
+四、劳动节：5月1日（周四）至5日（周一）放假调休，共5天。4月27日（周日）上班。
+五、端午节：5月31日（周六）至6月2日（周一）放假，共3天。
+六、国庆节、中秋节：10月1日（周三）至8日（周三）放假调休，共8天。9月28日（周日）、10月11日（周六）上班。
+";
+
+    #[test]
+    fn notice_2026() -> Result<()> {
+        let notice = parse_holiday_notice(NOTICE_2026, None)?;
+        assert_eq!(notice.year, 2026);
+        let out_dir = "../target/tmp/notice2026";
+        gen_holidays_csv(&notice, out_dir)?;
+        let generated = std::fs::read_to_string(Path::new(out_dir).join("holidays.csv"))?;
+        // 与顶层目录下的holidays.csv完全一致
+        let expected = include_str!("../../../holidays.csv").replace("\r\n", "\n");
+        assert_eq!(generated.trim_end(), expected.trim_end());
+        Ok(())
+    }
+
+    #[test]
+    fn notice_2025() -> Result<()> {
+        let notice = parse_holiday_notice(NOTICE_2025, None)?;
+        assert_eq!(notice.year, 2025);
+        let holidays: Vec<(String, &str)> = notice
+            .holidays
+            .iter()
+            .map(|h| (h.date.to_string(), h.name.as_str()))
+            .collect();
+        let expected = [
+            // 只放1天, 不调休
+            ("2025-01-01", "元旦"),
+            ("2025-01-28", "除夕"),
+            ("2025-01-29", "春节"),
+            ("2025-01-30", "春节"),
+            ("2025-01-31", "春节"),
+            ("2025-02-03", "春节"),
+            ("2025-02-04", "春节"),
+            ("2025-04-04", "清明"),
+            ("2025-05-01", "劳动节"),
+            ("2025-05-02", "劳动节"),
+            ("2025-05-05", "劳动节"),
+            ("2025-06-02", "端午"),
+            // 国庆、中秋合并放假, 中秋当天单独命名
+            ("2025-10-01", "国庆"),
+            ("2025-10-02", "国庆"),
+            ("2025-10-03", "国庆"),
+            ("2025-10-06", "中秋"),
+            ("2025-10-07", "国庆"),
+            ("2025-10-08", "国庆"),
+        ];
+        assert_eq!(
+            holidays,
+            expected
+                .iter()
+                .map(|(date, name)| (date.to_string(), *name))
+                .collect::<Vec<_>>()
+        );
+
+        let makeup_days: Vec<(String, &str)> = notice
+            .makeup_days
+            .iter()
+            .map(|(date, name)| (date.to_string(), name.as_str()))
+            .collect();
+        assert_eq!(
+            makeup_days,
+            vec![
+                ("2025-01-26".to_string(), "春节"),
+                ("2025-02-08".to_string(), "春节"),
+                ("2025-04-27".to_string(), "劳动节"),
+                ("2025-09-28".to_string(), "国庆"),
+                ("2025-10-11".to_string(), "国庆"),
+            ]
+        );
+        assert!(parse_holiday_notice("一、元旦：1月1日放假1天。", None).is_err());
+        Ok(())
+    }
+}
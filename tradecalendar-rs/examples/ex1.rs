@@ -1,9 +1,9 @@
 use anyhow::Result;
-use crossbeam_channel::{bounded, select, tick, Receiver};
+use crossbeam_channel::{after, bounded, select, Receiver};
 use std::time::Duration;
 
 use tradecalendar::{get_buildin_calendar, jcswitch::*};
-use tradecalendar::{TradeCalendar, TradingdayCache};
+use tradecalendar::{CalendarEvent, TradeCalendar, TradingdayCache};
 
 fn ctrl_channel() -> Result<Receiver<()>, ctrlc::Error> {
     let (sender, receiver) = bounded(100);
@@ -34,39 +34,48 @@ fn main() -> Result<()> {
     for tday in tday_slice {
         print!("{} ", tday.date);
     }
-    println!("\nevery 60 seconds timer started, or press Ctrl+C to exit\n");
+    println!("\ntimer started at each state transition, or press Ctrl+C to exit\n");
 
     let _ = calendar.time_changed(&now, true)?;
-    let mut trading = calendar.is_trading();
 
     let ctrl_c_events = ctrl_channel()?;
-    let ticks = tick(Duration::from_secs(60));
 
     loop {
+        // 休眠到下一个状态切换的时刻, 无需轮询
+        let transition = calendar.next_transition(&get_now(), true)?;
+        let secs = datetime_diff_secs(&get_now(), &transition.at).max(0) as u64;
+        println!("next transition at {}: {:?}", transition.at, transition.kinds);
+        let timer = after(Duration::from_secs(secs + 1));
+
         select! {
-            recv(ticks) -> _ => {
+            recv(timer) -> _ => {
                 let now = get_now();
-                let (old_tday, now_tday,old_date, now_date, opt_err) = calendar.time_changed(&now, true)?;
-                if let Some(err) = opt_err {
-                    println!("Error: {err}");
-                }
-                if old_date != now_date {
-                    println!("自然日改变: {old_date} => {now_date}");
-                }
-                if old_tday != now_tday {
-                    println!("交易日改变: {old_tday} => {now_tday}");
-                }
-                let now_trading = calendar.is_trading();
-                if trading != now_trading{
-                    println!("is_trading 改变: {trading} => {now_trading}");
-                    trading = now_trading;
-                    if trading {
-                        // do something here, such as:
-                        // check whether TraderApi is connected
-                        // ...
+                for event in calendar.time_changed_events(&now, true)? {
+                    match event {
+                        CalendarEvent::NaturalDayChanged { prev, curr } => {
+                            println!("自然日改变: {prev} => {curr}");
+                        }
+                        CalendarEvent::TradingDayChanged { prev, curr, next } => {
+                            println!("交易日改变: {curr}, prev {prev}, next {next}");
+                        }
+                        CalendarEvent::TradingStarted => {
+                            println!("is_trading 改变: false => true");
+                            // do something here, such as:
+                            // check whether TraderApi is connected
+                            // ...
+                        }
+                        CalendarEvent::TradingStopped => println!("is_trading 改变: true => false"),
+                        CalendarEvent::FailSafeUsed { reason } => println!("Error: {reason}"),
+                        CalendarEvent::CoverageEnding { days_left } => {
+                            println!("交易日历只剩{days_left}天, 请更新");
+                        }
                     }
                 }
-                println!("on_timer: {now}, tradingday {now_tday}, is_trading? {now_trading}\n");
+                println!(
+                    "on_timer: {now}, tradingday {}, is_trading? {}\n",
+                    calendar.current_tday(),
+                    calendar.is_trading()
+                );
             }
             recv(ctrl_c_events) -> _ => {
                 println!("\nGoodbye!");
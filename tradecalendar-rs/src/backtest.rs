@@ -0,0 +1,97 @@
+use anyhow::{Result, anyhow};
+
+use crate::jcswitch::*;
+use crate::tradecalendar::*;
+
+/// 回放的步进方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacktestStep {
+    /// 固定间隔, 单位秒, 必须大于0
+    Every(u32),
+    /// 直接跳到下一个状态切换时刻, 参见TradeCalendar::next_transition()
+    Transitions,
+}
+
+/// 回溯模式的驱动, 在[start, end]时间范围内步进, 每一步产生(时间点, 事件列表),
+/// 事件与实盘调用time_changed_events()时得到的相同
+///
+/// 第一步在start, 包含初始化产生的自然日、交易日改变等事件
+pub struct BacktestCalendar {
+    calendar: TradeCalendar,
+    start: MyDateTimeType,
+    end: MyDateTimeType,
+    step: BacktestStep,
+    fail_safe: bool,
+    /// 下一步的时间点, None表示已经结束
+    next: Option<MyDateTimeType>,
+}
+
+impl BacktestCalendar {
+    /// calendar会被reset(), 不要求事先调用time_changed()
+    ///
+    /// reset时不通知calendar上已注册的listener, listener从第一步开始收到事件
+    pub fn new(
+        mut calendar: TradeCalendar,
+        start: MyDateTimeType,
+        end: MyDateTimeType,
+        step: BacktestStep,
+    ) -> Result<Self> {
+        if start > end {
+            return Err(anyhow!("start {} is after end {}", start, end));
+        }
+        if step == BacktestStep::Every(0) {
+            return Err(anyhow!("step must be greater than 0"));
+        }
+        calendar.reset_silently(None)?;
+        Ok(Self {
+            calendar,
+            start,
+            end,
+            step,
+            fail_safe: false,
+            next: Some(start),
+        })
+    }
+
+    /// 设置交易日切换及is_trading判断的配置, 参见TradeCalendar::set_config()
+    pub fn set_config(&mut self, cfg: &TradingCheckConfig) -> Result<()> {
+        self.calendar.set_config(cfg)
+    }
+
+    /// 传递给time_changed()的fail_safe参数, 回溯模式缺省为false, 日历数据缺失时报错
+    pub fn set_fail_safe(&mut self, fail_safe: bool) {
+        self.fail_safe = fail_safe;
+    }
+
+    pub fn calendar(&self) -> &TradeCalendar {
+        &self.calendar
+    }
+
+    pub fn start(&self) -> &MyDateTimeType {
+        &self.start
+    }
+
+    pub fn end(&self) -> &MyDateTimeType {
+        &self.end
+    }
+
+    fn advance(&mut self, at: &MyDateTimeType) -> Result<Vec<CalendarEvent>> {
+        let events = self.calendar.time_changed_events(at, self.fail_safe)?;
+        let next = match self.step {
+            BacktestStep::Every(secs) => datetime_add_secs(at, secs as i64),
+            BacktestStep::Transitions => self.calendar.next_transition(at, self.fail_safe)?.at,
+        };
+        self.next = (next <= self.end).then_some(next);
+        Ok(events)
+    }
+}
+
+impl Iterator for BacktestCalendar {
+    type Item = Result<(MyDateTimeType, Vec<CalendarEvent>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at = self.next.take()?;
+        // 出错之后next为None, 迭代结束
+        Some(self.advance(&at).map(|events| (at, events)))
+    }
+}
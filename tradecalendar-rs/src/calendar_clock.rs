@@ -0,0 +1,148 @@
+use anyhow::Result;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use crate::jcswitch::*;
+use crate::tradecalendar::*;
+
+/// CalendarClock发布的当前状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarState {
+    pub time: MyDateTimeType,
+    pub prev_tday: MyDateType,
+    pub curr_tday: MyDateType,
+    pub next_tday: MyDateType,
+    pub is_trading: bool,
+}
+
+impl CalendarState {
+    fn of(calendar: &TradeCalendar) -> Self {
+        Self {
+            time: *calendar.current_time(),
+            prev_tday: *calendar.prev_tday(),
+            curr_tday: *calendar.current_tday(),
+            next_tday: *calendar.next_tday(),
+            is_trading: calendar.is_trading(),
+        }
+    }
+}
+
+/// 基于tokio的日历时钟服务, 持有TradeCalendar并自行驱动time_changed()
+///
+/// 在next_transition()计算出的切换时刻醒来, 而不是固定间隔轮询, 当前时间来自TradeCalendar::now();
+/// 事件通过broadcast通道发布, 当前状态通过watch通道发布
+pub struct CalendarClock {
+    calendar: TradeCalendar,
+    fail_safe: bool,
+    events: broadcast::Sender<CalendarEvent>,
+    state: watch::Sender<CalendarState>,
+}
+
+impl CalendarClock {
+    /// capacity: 事件通道的容量, 接收方处理过慢时会丢失较早的事件(RecvError::Lagged)
+    pub fn new(calendar: TradeCalendar, capacity: usize) -> Self {
+        let (events, _) = broadcast::channel(capacity);
+        let (state, _) = watch::channel(CalendarState::of(&calendar));
+        Self {
+            calendar,
+            fail_safe: true,
+            events,
+            state,
+        }
+    }
+
+    /// 传递给time_changed()的fail_safe参数, 缺省为true
+    pub fn set_fail_safe(&mut self, fail_safe: bool) {
+        self.fail_safe = fail_safe;
+    }
+
+    /// 订阅事件
+    pub fn subscribe(&self) -> broadcast::Receiver<CalendarEvent> {
+        self.events.subscribe()
+    }
+
+    /// 订阅当前状态
+    pub fn watch(&self) -> watch::Receiver<CalendarState> {
+        self.state.subscribe()
+    }
+
+    pub fn calendar(&self) -> &TradeCalendar {
+        &self.calendar
+    }
+
+    /// 将时间推进到datetime, 发布并返回产生的事件
+    pub fn step(&mut self, datetime: &MyDateTimeType) -> Result<Vec<CalendarEvent>> {
+        let events = self
+            .calendar
+            .time_changed_events(datetime, self.fail_safe)?;
+        for event in events.iter() {
+            // 没有订阅者时send()返回错误, 忽略即可
+            let _ = self.events.send(event.clone());
+        }
+        self.state.send_replace(CalendarState::of(&self.calendar));
+        Ok(events)
+    }
+
+    /// 持续运行直到stop收到true(或者stop的发送端被丢弃), 出错时返回
+    pub async fn run(&mut self, mut stop: watch::Receiver<bool>) -> Result<()> {
+        loop {
+            let now = self.calendar.now();
+            self.step(&now)?;
+            let transition = self.calendar.next_transition(&now, self.fail_safe)?;
+            let nanos = datetime_to_timestamp_nanos(&transition.at)
+                - datetime_to_timestamp_nanos(&self.calendar.now());
+            let sleep = tokio::time::sleep(self.calendar.real_duration(nanos));
+            tokio::select! {
+                _ = sleep => {}
+                changed = stop.changed() => {
+                    if changed.is_err() || *stop.borrow() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// 在当前tokio运行时中启动, 返回的句柄用来订阅及停止
+    pub fn spawn(mut self) -> CalendarClockHandle {
+        let (stop, stop_rx) = watch::channel(false);
+        let events = self.events.clone();
+        let state = self.state.subscribe();
+        let task = tokio::spawn(async move {
+            self.run(stop_rx).await?;
+            Ok(self)
+        });
+        CalendarClockHandle {
+            stop,
+            events,
+            state,
+            task,
+        }
+    }
+}
+
+/// CalendarClock::spawn()返回的句柄
+pub struct CalendarClockHandle {
+    stop: watch::Sender<bool>,
+    events: broadcast::Sender<CalendarEvent>,
+    state: watch::Receiver<CalendarState>,
+    task: JoinHandle<Result<CalendarClock>>,
+}
+
+impl CalendarClockHandle {
+    /// 订阅事件
+    pub fn subscribe(&self) -> broadcast::Receiver<CalendarEvent> {
+        self.events.subscribe()
+    }
+
+    /// 订阅当前状态
+    pub fn watch(&self) -> watch::Receiver<CalendarState> {
+        self.state.clone()
+    }
+
+    /// 停止服务, 取回CalendarClock
+    pub async fn stop(self) -> Result<CalendarClock> {
+        let _ = self.stop.send(true);
+        self.task.await?
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Result, anyhow};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::exchange::ExchangeTimeZone;
+use crate::jcswitch::*;
+
+/// 时钟, TradeCalendar及CalendarClock通过它获取当前时间, 便于测试及模拟
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> MyDateTimeType;
+
+    /// 时钟经过nanos纳秒, 需要等待的真实时间, 缺省两者相同
+    fn real_duration(&self, nanos: i64) -> Duration {
+        Duration::from_nanos(nanos.max(0) as u64)
+    }
+}
+
+/// 系统时钟, 即get_now()
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> MyDateTimeType {
+        get_now()
+    }
+}
+
+/// 交易所时区的系统时钟, 与运行机器的时区无关, 适用于时区为UTC的服务器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExchangeClock(pub ExchangeTimeZone);
+
+impl Clock for ExchangeClock {
+    fn now(&self) -> MyDateTimeType {
+        self.0.now()
+    }
+}
+
+/// 固定不变的时钟
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub MyDateTimeType);
+
+impl Clock for FixedClock {
+    fn now(&self) -> MyDateTimeType {
+        self.0
+    }
+}
+
+/// 手动设置或推进的时钟
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<MyDateTimeType>,
+}
+
+impl ManualClock {
+    pub fn new(start: MyDateTimeType) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    /// 设置为datetime, 可以往回设置
+    pub fn set(&self, datetime: MyDateTimeType) {
+        *self.now.lock().expect("poisoned") = datetime;
+    }
+
+    /// 推进secs秒
+    pub fn advance_secs(&self, secs: i64) {
+        let mut now = self.now.lock().expect("poisoned");
+        *now = datetime_add_secs(&now, secs);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> MyDateTimeType {
+        *self.now.lock().expect("poisoned")
+    }
+}
+
+/// 回放时钟, 从start开始, 按真实时间的speed倍速前进
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayClock {
+    start: MyDateTimeType,
+    origin: Instant,
+    speed: f64,
+}
+
+impl ReplayClock {
+    /// speed: 倍速, 必须为大于0的有限值, 否则返回错误
+    pub fn new(start: MyDateTimeType, speed: f64) -> Result<Self> {
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(anyhow!(
+                "speed must be a finite value greater than 0, got {}",
+                speed
+            ));
+        }
+        Ok(Self {
+            start,
+            origin: Instant::now(),
+            speed,
+        })
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+}
+
+impl Clock for ReplayClock {
+    fn now(&self) -> MyDateTimeType {
+        let elapsed = self.origin.elapsed().as_nanos() as f64 * self.speed;
+        datetime_from_timestamp_nanos(datetime_to_timestamp_nanos(&self.start) + elapsed as i64)
+    }
+
+    fn real_duration(&self, nanos: i64) -> Duration {
+        Duration::from_nanos((nanos.max(0) as f64 / self.speed) as u64)
+    }
+}
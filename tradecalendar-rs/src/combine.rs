@@ -0,0 +1,105 @@
+use anyhow::{Result, anyhow};
+
+use crate::holiday::{regular_nights, relink_tradingdays_with};
+use crate::tradecalendar::{StaticCalendar, Tradingday, TradingdayCache, search_days};
+
+/// 两个日历合并的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalendarSetOp {
+    /// 两者都是交易日, 如跨市场套利要求两个市场都开市
+    Intersection,
+    /// 任一是交易日
+    Union,
+    /// 前者是交易日, 后者不是
+    Difference,
+}
+
+/// 按op合并两个日历, 结果为两者日期范围的交集, next重新计算, 参见relink_tradingdays()
+///
+/// night按op合并后, 后一交易日不相邻的将被去掉; 合并后重新相邻的交易日(如只有b交易的前一天),
+/// 按两者常规的夜盘(同样按op合并, 参见regular_nights())恢复; morning由前一自然日的night决定
+pub fn combine_calendars<A, B>(a: &A, b: &B, op: CalendarSetOp) -> Result<StaticCalendar>
+where
+    A: TradingdayCache + ?Sized,
+    B: TradingdayCache + ?Sized,
+{
+    let (a_list, b_list) = (a.get_full_day_list(), b.get_full_day_list());
+    let (a_regular, b_regular) = (regular_nights(a_list), regular_nights(b_list));
+    let mut result: Vec<Tradingday> = Vec::new();
+    let mut regular: Vec<bool> = Vec::new();
+    for (a_index, day_a) in a_list.iter().enumerate() {
+        let (_, index, _) = search_days(b_list, &day_a.date);
+        if index < 0 {
+            continue;
+        }
+        let day_b = &b_list[index as usize];
+        let (regular_a, regular_b) = (a_regular[a_index], b_regular[index as usize]);
+        let (trading, night, next) = match op {
+            CalendarSetOp::Intersection => {
+                regular.push(regular_a && regular_b);
+                (
+                    day_a.trading && day_b.trading,
+                    day_a.night && day_b.night,
+                    day_a.next.max(day_b.next),
+                )
+            }
+            CalendarSetOp::Union => {
+                regular.push(regular_a || regular_b);
+                (
+                    day_a.trading || day_b.trading,
+                    day_a.night || day_b.night,
+                    day_a.next.min(day_b.next),
+                )
+            }
+            CalendarSetOp::Difference => {
+                regular.push(regular_a);
+                (day_a.trading && !day_b.trading, day_a.night, day_a.next)
+            }
+        };
+        result.push(Tradingday {
+            date: day_a.date,
+            morning: false,
+            trading,
+            night,
+            next,
+        });
+    }
+    if result.is_empty() {
+        return Err(anyhow!(
+            "combine_calendars(): no common dates between ({:?} ~ {:?}) and ({:?} ~ {:?})",
+            a.min_date(),
+            a.max_date(),
+            b.min_date(),
+            b.max_date()
+        ));
+    }
+    relink_tradingdays_with(&mut result, &regular);
+    StaticCalendar::new(result)
+}
+
+/// 两者都是交易日, 参见combine_calendars()
+pub fn intersect_calendars<A, B>(a: &A, b: &B) -> Result<StaticCalendar>
+where
+    A: TradingdayCache + ?Sized,
+    B: TradingdayCache + ?Sized,
+{
+    combine_calendars(a, b, CalendarSetOp::Intersection)
+}
+
+/// 任一是交易日, 参见combine_calendars()
+pub fn union_calendars<A, B>(a: &A, b: &B) -> Result<StaticCalendar>
+where
+    A: TradingdayCache + ?Sized,
+    B: TradingdayCache + ?Sized,
+{
+    combine_calendars(a, b, CalendarSetOp::Union)
+}
+
+/// a是交易日而b不是, 参见combine_calendars()
+pub fn difference_calendars<A, B>(a: &A, b: &B) -> Result<StaticCalendar>
+where
+    A: TradingdayCache + ?Sized,
+    B: TradingdayCache + ?Sized,
+{
+    combine_calendars(a, b, CalendarSetOp::Difference)
+}
@@ -0,0 +1,405 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::hkex::{
+    close_tradingdays, hkex_buildin_overrides, stock_connect_tradingdays, tradingdays_for_hkex,
+};
+use crate::holiday::{HolidayTable, cfets_tradingdays_to_calendar};
+use crate::jcswitch::*;
+use crate::overrides::CalendarOverrides;
+use crate::session::SessionTemplate;
+use crate::tradecalendar::*;
+
+/// 交易所
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Exchange {
+    /// 上海期货交易所
+    SHFE,
+    /// 上海国际能源交易中心
+    INE,
+    /// 大连商品交易所
+    DCE,
+    /// 郑州商品交易所
+    CZCE,
+    /// 中国金融期货交易所
+    CFFEX,
+    /// 广州期货交易所
+    GFEX,
+    /// 上海证券交易所
+    SSE,
+    /// 深圳证券交易所
+    SZSE,
+    /// 中国外汇交易中心(银行间市场), 调休上班日也交易
+    CFETS,
+    /// 香港交易所, 按香港公众假期休市
+    HKEX,
+}
+
+impl Exchange {
+    pub const ALL: [Exchange; 10] = [
+        Exchange::SHFE,
+        Exchange::INE,
+        Exchange::DCE,
+        Exchange::CZCE,
+        Exchange::CFFEX,
+        Exchange::GFEX,
+        Exchange::SSE,
+        Exchange::SZSE,
+        Exchange::CFETS,
+        Exchange::HKEX,
+    ];
+
+    /// 交易所代码, 与CTP的ExchangeID一致
+    pub fn code(&self) -> &'static str {
+        match self {
+            Exchange::SHFE => "SHFE",
+            Exchange::INE => "INE",
+            Exchange::DCE => "DCE",
+            Exchange::CZCE => "CZCE",
+            Exchange::CFFEX => "CFFEX",
+            Exchange::GFEX => "GFEX",
+            Exchange::SSE => "SSE",
+            Exchange::SZSE => "SZSE",
+            Exchange::CFETS => "CFETS",
+            Exchange::HKEX => "HKEX",
+        }
+    }
+
+    /// 夜盘开始的日期(该日晚上第一次有夜盘), None表示没有夜盘
+    ///
+    /// 上期所黄金白银2013-07-05, 大商所2014-07-04, 郑商所2014-12-12, 能源中心原油上市即有夜盘;
+    /// 中金所、广期所、股票、银行间市场及港交所没有夜盘
+    pub fn night_start(&self) -> Option<MyDateType> {
+        match self {
+            Exchange::SHFE => Some(make_date(2013, 7, 5)),
+            Exchange::INE => Some(make_date(2018, 3, 26)),
+            Exchange::DCE => Some(make_date(2014, 7, 4)),
+            Exchange::CZCE => Some(make_date(2014, 12, 12)),
+            Exchange::CFFEX
+            | Exchange::GFEX
+            | Exchange::SSE
+            | Exchange::SZSE
+            | Exchange::CFETS
+            | Exchange::HKEX => None,
+        }
+    }
+
+    /// 交易所所在时区
+    pub fn time_zone(&self) -> ExchangeTimeZone {
+        match self {
+            Exchange::HKEX => ExchangeTimeZone::HongKong,
+            _ => ExchangeTimeZone::Shanghai,
+        }
+    }
+
+    /// 在the_day当晚是否可能有夜盘(不考虑节假日)
+    pub fn has_night_on(&self, the_day: &MyDateType) -> bool {
+        self.night_start().is_some_and(|start| the_day >= &start)
+    }
+}
+
+impl Display for Exchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Exchange {
+    type Err = anyhow::Error;
+
+    /// 不区分大小写, 支持 SH/SSE, SZ/SZSE, IB/CFETS, HK/HKEX 两种写法
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "SHFE" => Ok(Exchange::SHFE),
+            "INE" => Ok(Exchange::INE),
+            "DCE" => Ok(Exchange::DCE),
+            "CZCE" => Ok(Exchange::CZCE),
+            "CFFEX" => Ok(Exchange::CFFEX),
+            "GFEX" => Ok(Exchange::GFEX),
+            "SSE" | "SH" => Ok(Exchange::SSE),
+            "SZSE" | "SZ" => Ok(Exchange::SZSE),
+            "CFETS" | "IB" => Ok(Exchange::CFETS),
+            "HKEX" | "HK" => Ok(Exchange::HKEX),
+            _ => Err(anyhow!("unknown exchange `{}`", s)),
+        }
+    }
+}
+
+/// 交易所所在时区, 交易日历及交易时段都按交易所的本地时间计算
+///
+/// 中国大陆及香港都没有夏令时, 均为固定的UTC+8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+pub enum ExchangeTimeZone {
+    /// Asia/Shanghai
+    #[default]
+    Shanghai,
+    /// Asia/Hong_Kong
+    HongKong,
+}
+
+impl ExchangeTimeZone {
+    /// IANA时区名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExchangeTimeZone::Shanghai => "Asia/Shanghai",
+            ExchangeTimeZone::HongKong => "Asia/Hong_Kong",
+        }
+    }
+
+    /// 相对UTC的偏移秒数
+    pub fn utc_offset_secs(&self) -> i32 {
+        match self {
+            ExchangeTimeZone::Shanghai | ExchangeTimeZone::HongKong => 8 * 3600,
+        }
+    }
+
+    /// UTC时间戳(纳秒)转换为本时区的本地时间
+    pub fn from_utc_nanos(&self, nanos: i64) -> MyDateTimeType {
+        utc_nanos_to_local(nanos, self.utc_offset_secs())
+    }
+
+    /// 带时区的日期时间转换为本时区的本地时间
+    pub fn from_zoned(&self, input: &MyZonedType) -> MyDateTimeType {
+        zoned_to_local(input, self.utc_offset_secs())
+    }
+
+    /// 本时区的当前时间, 与运行机器的时区无关
+    pub fn now(&self) -> MyDateTimeType {
+        self.from_utc_nanos(now_utc_nanos())
+    }
+}
+
+impl Display for ExchangeTimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// 将(以上期所为准的)期货日历转换为某个交易所的日历,
+///
+/// 各交易所的节假日安排相同, 区别仅在于夜盘: 夜盘开始前及无夜盘的交易所, night都为false,
+/// morning(凌晨盘)由前一自然日的night决定, 需要重新计算.
+/// 港交所的节假日不同, 使用tradingdays_for_hkex()
+pub fn tradingdays_for_exchange(full_list: &[Tradingday], exchange: Exchange) -> Vec<Tradingday> {
+    let mut result: Vec<Tradingday> = Vec::with_capacity(full_list.len());
+    for td in full_list.iter() {
+        let mut day = td.clone();
+        day.night = td.night && exchange.has_night_on(&td.date);
+        day.morning = match result.last() {
+            Some(prev) if prev.date == yesterday(&td.date) => prev.night,
+            // 第一条数据或者数据不连续, 只能按原数据及夜盘开始日期判断
+            _ => td.morning && exchange.has_night_on(&yesterday(&td.date)),
+        };
+        result.push(day);
+    }
+    result
+}
+
+/// 银行间市场(CFETS)的日历: 节假日与交易所相同, 但调休上班日也交易, 没有夜盘
+///
+/// 按cfets_tradingdays_to_calendar()逐年重新生成, full_list中有table没有数据的年份时报错, 因为无法识别调休上班日
+pub fn tradingdays_for_cfets(
+    full_list: &[Tradingday],
+    table: &HolidayTable,
+) -> Result<Vec<Tradingday>> {
+    let mut years: Vec<i32> = full_list.iter().map(|d| date_ymd(&d.date).0).collect();
+    years.dedup();
+    if let Some(year) = years.iter().find(|year| !table.contains_year(**year)) {
+        return Err(anyhow!(
+            "tradingdays_for_cfets(): no holiday data of {} in table",
+            year
+        ));
+    }
+    let mut result = tradingdays_for_exchange(full_list, Exchange::CFETS);
+    let holidays = table.holidays();
+    let makeup_days = table.makeup_days();
+    for year in table.years() {
+        let in_year = |date: &&MyDateType| date_ymd(date).0 == year;
+        let year_holidays: Vec<MyDateType> = holidays
+            .iter()
+            .map(|h| &h.date)
+            .filter(in_year)
+            .copied()
+            .collect();
+        if year_holidays.is_empty() {
+            continue;
+        }
+        let year_makeup_days: Vec<MyDateType> =
+            makeup_days.iter().filter(in_year).copied().collect();
+        for day in cfets_tradingdays_to_calendar(&year_holidays, &year_makeup_days)? {
+            let (_, index, _) = search_days(&result, &day.date);
+            if index >= 0 {
+                result[index as usize] = day;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// 按交易所分别维护的交易日历集合, 每个交易所一份Tradingday列表
+#[derive(Debug, Clone, Default)]
+pub struct CalendarRegistry {
+    calendars: HashMap<Exchange, StaticCalendar>,
+}
+
+impl CalendarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从一份期货日历(如内置的calendar.csv)生成所有交易所的日历,
+    /// 其中银行间市场缺少调休上班日, 可以再调用insert_cfets(); 港交所按香港公众假期生成相同日期范围的日历
+    pub fn from_tradingdays(full_list: &[Tradingday]) -> Result<Self> {
+        let (Some(first), Some(last)) = (full_list.first(), full_list.last()) else {
+            return Err(anyhow!("CalendarRegistry: full_list is empty."));
+        };
+        let (first, last) = (first.date, last.date);
+        let mut registry = Self::new();
+        for exchange in Exchange::ALL {
+            let calendar = match exchange {
+                // 内置的临时假期已经应用在日历上, 同样保留记录
+                Exchange::HKEX => {
+                    let applied = hkex_buildin_overrides()
+                        .items()
+                        .iter()
+                        .filter(|o| o.date >= first && o.date <= last)
+                        .cloned()
+                        .collect();
+                    StaticCalendar::new(tradingdays_for_hkex(&first, &last)?)?
+                        .with_overrides(applied)
+                }
+                _ => StaticCalendar::new(tradingdays_for_exchange(full_list, exchange))?,
+            };
+            registry.calendars.insert(exchange, calendar);
+        }
+        Ok(registry)
+    }
+
+    /// 某交易所临时停市(如台风、黑雨), dates设置为非交易日, 并重新计算next等
+    pub fn close_days(&mut self, exchange: Exchange, dates: &[MyDateType]) -> Result<()> {
+        let calendar = self.get(exchange)?;
+        let mut full_list = calendar.get_full_day_list().clone();
+        let overrides = calendar.overrides().to_vec();
+        close_tradingdays(&mut full_list, dates);
+        let calendar = StaticCalendar::new(full_list)?.with_overrides(overrides);
+        self.calendars.insert(exchange, calendar);
+        Ok(())
+    }
+
+    /// 在某交易所的日历上应用临时调整, 返回实际生效的记录数, 参见CalendarOverrides::apply()
+    ///
+    /// 生效的记录保留在日历上, 参见StaticCalendar::overrides()
+    pub fn apply_overrides(
+        &mut self,
+        exchange: Exchange,
+        overrides: &CalendarOverrides,
+    ) -> Result<usize> {
+        let calendar = self.get(exchange)?;
+        let mut full_list = calendar.get_full_day_list().clone();
+        let mut applied = calendar.overrides().to_vec();
+        let new_applied = overrides.apply(&mut full_list);
+        let count = new_applied.len();
+        applied.extend(new_applied);
+        applied.sort_by_key(|o| o.date);
+        let calendar = StaticCalendar::new(full_list)?.with_overrides(applied);
+        self.calendars.insert(exchange, calendar);
+        Ok(count)
+    }
+
+    /// 沪深港通的日历, 由上交所和港交所的日历生成, 参见stock_connect_tradingdays()
+    pub fn stock_connect(&self) -> Result<StaticCalendar> {
+        let mainland = self.get(Exchange::SSE)?.get_full_day_list();
+        let hkex = self.get(Exchange::HKEX)?.get_full_day_list();
+        StaticCalendar::new(stock_connect_tradingdays(mainland, hkex))
+    }
+
+    /// 由期货日历及节假日(含调休上班日)生成银行间市场(CFETS)的日历, 添加或替换原有的
+    pub fn insert_cfets(&mut self, full_list: &[Tradingday], table: &HolidayTable) -> Result<()> {
+        self.insert(Exchange::CFETS, tradingdays_for_cfets(full_list, table)?)
+    }
+
+    /// 添加或替换某个交易所的日历
+    pub fn insert(&mut self, exchange: Exchange, full_list: Vec<Tradingday>) -> Result<()> {
+        let calendar = StaticCalendar::new(full_list)?;
+        self.calendars.insert(exchange, calendar);
+        Ok(())
+    }
+
+    /// 获取某个交易所的日历
+    pub fn get(&self, exchange: Exchange) -> Result<&StaticCalendar> {
+        self.calendars
+            .get(&exchange)
+            .ok_or_else(|| anyhow!("calendar for {} not loaded", exchange))
+    }
+
+    /// 已加载的交易所
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        let mut list: Vec<Exchange> = self.calendars.keys().copied().collect();
+        list.sort();
+        list
+    }
+
+    /// 某交易所trade_day是否交易日
+    pub fn is_trading_day(&self, exchange: Exchange, trade_day: &MyDateType) -> Result<bool> {
+        self.get(exchange)?.is_trading_day(trade_day)
+    }
+
+    /// 某交易所后续第num个交易日, 要求num大于零
+    pub fn get_next_trading_day(
+        &self,
+        exchange: Exchange,
+        the_day: &MyDateType,
+        num: usize,
+    ) -> Result<&Tradingday> {
+        self.get(exchange)?.get_next_trading_day(the_day, num)
+    }
+
+    /// 某交易所之前的第num个交易日, 要求num大于零
+    pub fn get_prev_trading_day(
+        &self,
+        exchange: Exchange,
+        the_day: &MyDateType,
+        num: usize,
+    ) -> Result<&Tradingday> {
+        self.get(exchange)?.get_prev_trading_day(the_day, num)
+    }
+
+    /// 根据输入时间获取某交易所的交易日, 参见TradingdayCache::trading_day_from_datetime()
+    pub fn trading_day_from_datetime(
+        &self,
+        exchange: Exchange,
+        input: &MyDateTimeType,
+        method: NotTradingSearchMethod,
+        session: &SessionTemplate,
+    ) -> Result<MyDateType> {
+        self.get(exchange)?
+            .trading_day_from_datetime(input, method, session)
+    }
+
+    /// 根据UTC时间戳(纳秒)获取某交易所的交易日, 先转换为交易所时区的本地时间
+    pub fn trading_day_from_utc_nanos(
+        &self,
+        exchange: Exchange,
+        nanos: i64,
+        method: NotTradingSearchMethod,
+        session: &SessionTemplate,
+    ) -> Result<MyDateType> {
+        let input = exchange.time_zone().from_utc_nanos(nanos);
+        self.trading_day_from_datetime(exchange, &input, method, session)
+    }
+
+    /// 根据CTP行情的TradingDay和UpdateTime还原tick的自然日时间, 参见TradingdayCache::action_datetime()
+    pub fn action_datetime(
+        &self,
+        exchange: Exchange,
+        trading_day: &MyDateType,
+        update_time: &MyTimeType,
+    ) -> Result<MyDateTimeType> {
+        self.get(exchange)?
+            .action_datetime(trading_day, update_time, exchange)
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{Result, anyhow};
+
+use crate::holiday::{
+    Holiday, add_days, lunar_to_solar, qingming, relink_tradingdays, weekday_index,
+};
+use crate::jcswitch::*;
+use crate::overrides::CalendarOverrides;
+use crate::tradecalendar::{Tradingday, search_days};
+
+/// 政府临时宣布的公众假期(不在《公众假期条例》的常规假期中), 需要手工维护
+const HK_SPECIAL_HOLIDAYS: [(i32, u32, u32, &str); 1] = [(
+    2015,
+    9,
+    3,
+    "中国人民抗日战争暨世界反法西斯战争胜利70周年纪念日",
+)];
+
+/// 复活节(西方教会, 格里高利历), 使用匿名格里高利算法
+pub fn easter_sunday(year: i32) -> MyDateType {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    make_date(year, month as u32, day as u32)
+}
+
+/// 按《公众假期条例》计算香港year年的公众假期(含周六, 不含周日), 按日期排序
+///
+/// 假期适逢周日, 则顺延至下一个非假期的日子, 名称为"xx翌日"; 农历年初一至初三有一天为周日, 则初四补假;
+/// 中秋节翌日适逢周日, 同样顺延(如2022-09-12); 圣诞节后第一个周日以外的日子为假期.
+/// 不包括政府临时宣布的假期
+pub fn hk_public_holidays(year: i32) -> Result<Vec<Holiday>> {
+    let lunar = |month, day| lunar_to_solar(year, month, day, false);
+    let easter = easter_sunday(year);
+    let lunar_new_year = lunar(1, 1)?;
+    let mid_autumn = lunar(8, 16)?;
+    let mut boxing_day = make_date(year, 12, 26);
+    if weekday_index(&boxing_day) == 6 {
+        boxing_day = make_date(year, 12, 27);
+    }
+
+    let mut days: Vec<(MyDateType, &str)> = vec![
+        (make_date(year, 1, 1), "元旦"),
+        (lunar_new_year, "农历年初一"),
+        (add_days(&lunar_new_year, 1), "农历年初二"),
+        (add_days(&lunar_new_year, 2), "农历年初三"),
+        (qingming(year)?, "清明节"),
+        (add_days(&easter, -2), "耶稣受难节"),
+        (add_days(&easter, -1), "耶稣受难节翌日"),
+        (add_days(&easter, 1), "复活节星期一"),
+        (make_date(year, 5, 1), "劳动节"),
+        (lunar(4, 8)?, "佛诞"),
+        (lunar(5, 5)?, "端午节"),
+        (make_date(year, 7, 1), "香港特别行政区成立纪念日"),
+        (mid_autumn, "中秋节翌日"),
+        (make_date(year, 10, 1), "国庆日"),
+        (lunar(9, 9)?, "重阳节"),
+        (make_date(year, 12, 25), "圣诞节"),
+        (boxing_day, "圣诞节后第一个周日以外的日子"),
+    ];
+    days.sort_by_key(|(date, _)| *date);
+
+    // 先确定不需要顺延的假期, 再按日期顺序处理需要顺延的
+    let mut taken: Vec<MyDateType> = days
+        .iter()
+        .filter(|(date, _)| weekday_index(date) != 6)
+        .map(|(date, _)| *date)
+        .collect();
+    let mut result: Vec<Holiday> = Vec::with_capacity(days.len());
+    for (date, name) in days {
+        if weekday_index(&date) != 6 && !result.iter().any(|h| h.date == date) {
+            result.push(Holiday {
+                date,
+                name: name.to_string(),
+            });
+            continue;
+        }
+        let mut moved = date;
+        while weekday_index(&moved) == 6 || taken.contains(&moved) {
+            moved = add_days(&moved, 1);
+        }
+        taken.push(moved);
+        // 原来的名称保留在原来的日期上, 补假另外命名
+        let name = if date >= lunar_new_year && date <= add_days(&lunar_new_year, 2) {
+            "农历年初四".to_string()
+        } else if date == mid_autumn {
+            "中秋节后第二日".to_string()
+        } else {
+            format!("{}翌日", name)
+        };
+        result.push(Holiday { date: moved, name });
+    }
+    result.sort_by_key(|h| h.date);
+    Ok(result)
+}
+
+/// 港交所内置的临时休市: 政府临时宣布的公众假期, 不含台风、黑雨停市
+///
+/// 只包含已知的日期, 以后新宣布的假期需要另外用CalendarOverrides添加
+pub fn hkex_buildin_overrides() -> CalendarOverrides {
+    let mut overrides = CalendarOverrides::new();
+    for (year, month, day, name) in HK_SPECIAL_HOLIDAYS {
+        overrides.close_day(make_date(year, month, day), name);
+    }
+    overrides
+}
+
+/// 香港交易所[start, end]之间的Tradingday列表, 周一至周五除公众假期外都是交易日, 没有夜盘
+///
+/// 政府临时宣布的假期只包含hkex_buildin_overrides()中已知的日期;
+/// 除夕、圣诞前夕及新年前夕只有半日市, 仍然是交易日; 台风、黑雨停市使用close_tradingdays()
+pub fn tradingdays_for_hkex(start: &MyDateType, end: &MyDateType) -> Result<Vec<Tradingday>> {
+    if start > end {
+        return Err(anyhow!(
+            "tradingdays_for_hkex(): start {} > end {}",
+            start,
+            end
+        ));
+    }
+    let mut holidays: Vec<MyDateType> = Vec::new();
+    for year in date_ymd(start).0..=date_ymd(end).0 + 1 {
+        holidays.extend(hk_public_holidays(year)?.into_iter().map(|h| h.date));
+    }
+    let is_trading = |date: &MyDateType| weekday_index(date) < 5 && !holidays.contains(date);
+
+    // 最后一个交易日之后的日期, next取end之后的第一个交易日
+    let mut next = tomorrow(end);
+    while !is_trading(&next) {
+        next = tomorrow(&next);
+    }
+    let mut result: Vec<Tradingday> = Vec::new();
+    let mut the_day = *start;
+    while &the_day <= end {
+        result.push(Tradingday {
+            date: the_day,
+            morning: false,
+            trading: is_trading(&the_day),
+            night: false,
+            next,
+        });
+        the_day = tomorrow(&the_day);
+    }
+    // 应用临时假期, 同时重新计算next等
+    hkex_buildin_overrides().apply(&mut result);
+    Ok(result)
+}
+
+/// 将dates设置为非交易日(如台风、黑雨停市), 并重新计算next等, 参见relink_tradingdays()
+///
+/// 不在list范围内的日期将被忽略
+pub fn close_tradingdays(list: &mut [Tradingday], dates: &[MyDateType]) {
+    for date in dates {
+        let (_, index, _) = search_days(list, date);
+        if index >= 0 {
+            list[index as usize].trading = false;
+        }
+    }
+    relink_tradingdays(list);
+}
+
+/// 沪深港通的Tradingday列表: 两地都开市, 且两地的下一交易日对方也开市(可以完成交收)时, 才是交易日
+///
+/// mainland为上交所或深交所的日历, hkex为港交所的日历, 结果为两者日期范围的交集, 没有夜盘
+pub fn stock_connect_tradingdays(mainland: &[Tradingday], hkex: &[Tradingday]) -> Vec<Tradingday> {
+    // 超出日历范围的日期无法判断, 视为开市
+    let is_open = |list: &[Tradingday], date: &MyDateType| {
+        let (_, index, _) = search_days(list, date);
+        match index {
+            -1 => list.last().is_some_and(|last| date > &last.date),
+            _ => list[index as usize].trading,
+        }
+    };
+    let mut result: Vec<Tradingday> = Vec::new();
+    for day in mainland.iter() {
+        let (_, index, _) = search_days(hkex, &day.date);
+        if index < 0 {
+            continue;
+        }
+        let hk_day = &hkex[index as usize];
+        let trading = day.trading
+            && hk_day.trading
+            && is_open(hkex, &day.next)
+            && is_open(mainland, &hk_day.next);
+        result.push(Tradingday {
+            date: day.date,
+            morning: false,
+            trading,
+            night: false,
+            next: day.next.max(hk_day.next),
+        });
+    }
+    relink_tradingdays(&mut result);
+    result
+}
@@ -0,0 +1,644 @@
+use anyhow::{Context, Result, anyhow};
+use csv::{Reader, Writer};
+use encoding_rs_io::DecodeReaderBytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use crate::jcswitch::*;
+use crate::tradecalendar::{Tradingday, next_working_day};
+
+/// LUNAR_INFO第一项对应的农历年份
+const LUNAR_FIRST_YEAR: i32 = 2000;
+
+/// 农历2000~2100年的数据, 每年一项, 由天文算法(定朔、定气)计算, 已与香港天文台公布的闰月核对
+///
+/// bit 0~11: 1~12月是否为大月(30天), bit 12~15: 闰几月(0表示无闰月), bit 16: 闰月是否为大月,
+/// bit 17~21: 正月初一距离公历1月21日的天数
+#[rustfmt::skip]
+const LUNAR_INFO: [u32; 101] = [
+    0x01e0693, 0x0064a9b, 0x02c052b, 0x0160a5b, 0x0022aae, 0x026056a, 0x0107dd5, 0x0380ba4,
+    0x0220b49, 0x00a5d53, 0x0300a95, 0x01a052d, 0x004455d, 0x0280ab5, 0x0149baa, 0x03a05d2,
+    0x0240da5, 0x00f6e8a, 0x0340d4a, 0x01e0c95, 0x0084a9e, 0x02c0556, 0x0160ab5, 0x0022ada,
+    0x02806d2, 0x0106765, 0x0360725, 0x020064b, 0x00a5657, 0x02e0cab, 0x01a055a, 0x004356e,
+    0x02a0b69, 0x014bf52, 0x03a0b52, 0x0240b25, 0x00f6d0b, 0x0320a4b, 0x01c04ab, 0x00652bb,
+    0x02c05ad, 0x0160b6a, 0x0022daa, 0x0280d92, 0x0127ea5, 0x0360d25, 0x0200a55, 0x00b5a4d,
+    0x03004b6, 0x01805b5, 0x00536d2, 0x02a0ec9, 0x0168f92, 0x03a0e92, 0x0240d26, 0x00f6516,
+    0x0320a57, 0x01c0556, 0x0074365, 0x02c0755, 0x0180749, 0x000374b, 0x0260693, 0x0107aab,
+    0x036052b, 0x01e0a5b, 0x00a5aba, 0x030056a, 0x01a0b65, 0x0044baa, 0x02a0b4a, 0x0148d95,
+    0x03a0a95, 0x022052d, 0x00c656d, 0x0320ab5, 0x01e05aa, 0x00645d5, 0x02c0da5, 0x0180d4a,
+    0x0023e4d, 0x0260c96, 0x0107cce, 0x0360556, 0x0200ab5, 0x00b5ad2, 0x03006d2, 0x01a0ea5,
+    0x006472a, 0x028068b, 0x0128697, 0x03804ab, 0x022055b, 0x00d6556, 0x0320b6a, 0x01e0752,
+    0x0084b95, 0x02c0b45, 0x0160a8b, 0x0002a4f, 0x02604ab,
+];
+
+/// 节假日, 与holidays.csv的一行对应, 仅包含周一至周五
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Holiday {
+    #[serde(rename = "_date")]
+    pub date: MyDateType,
+    #[serde(rename = "_name")]
+    pub name: String,
+}
+
+/// 日期的类别, 与Tradingday互为补充
+///
+/// 调休上班的周末交易所休市, 但银行间市场和银行正常营业
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DayKind {
+    /// 周一至周五的工作日
+    Weekday,
+    /// 周六周日
+    Weekend,
+    /// 法定节假日(仅周一至周五)
+    Holiday { name: String },
+    /// 调休上班的周六周日
+    MakeupWorkday,
+}
+
+impl DayKind {
+    /// 银行等是否营业, 包括调休上班日
+    pub fn is_working_day(&self) -> bool {
+        matches!(self, DayKind::Weekday | DayKind::MakeupWorkday)
+    }
+
+    /// 交易所是否开市, 调休上班日交易所休市
+    pub fn is_exchange_open(&self) -> bool {
+        matches!(self, DayKind::Weekday)
+    }
+}
+
+/// 连续的休息日(节假日及相连的周末), 如国庆7天长假
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HolidayBlock {
+    /// 出现最多的节假日名称, 如除夕+春节为春节
+    pub name: String,
+    pub first_day: MyDateType,
+    pub last_day: MyDateType,
+    /// 天数, 包括周末
+    pub length: usize,
+}
+
+/// date所在的连续休息日, is_off判断是否休息, holiday_name返回周一至周五的节假日名称, 不含节假日的普通周末返回None
+pub(crate) fn find_holiday_block(
+    date: &MyDateType,
+    is_off: impl Fn(&MyDateType) -> bool,
+    holiday_name: impl Fn(&MyDateType) -> Option<String>,
+) -> Option<HolidayBlock> {
+    if !is_off(date) {
+        return None;
+    }
+    let mut first_day = *date;
+    while is_off(&add_days(&first_day, -1)) {
+        first_day = add_days(&first_day, -1);
+    }
+    let mut last_day = *date;
+    while is_off(&add_days(&last_day, 1)) {
+        last_day = add_days(&last_day, 1);
+    }
+
+    // 按出现次数取名称, 次数相同取先出现的
+    let mut names: Vec<(String, usize)> = Vec::new();
+    let mut the_day = first_day;
+    while the_day <= last_day {
+        if let Some(name) = holiday_name(&the_day) {
+            match names.iter_mut().find(|(n, _)| n == &name) {
+                Some((_, count)) => *count += 1,
+                None => names.push((name, 1)),
+            }
+        }
+        the_day = add_days(&the_day, 1);
+    }
+    let max_count = names.iter().map(|(_, count)| *count).max()?;
+    let name = names.into_iter().find(|(_, count)| *count == max_count)?.0;
+    Some(HolidayBlock {
+        name,
+        first_day,
+        last_day,
+        length: (date_to_days_since_epoch(&last_day) - date_to_days_since_epoch(&first_day) + 1)
+            as usize,
+    })
+}
+
+/// 扩展的holidays.csv的一行, _kind为makeup表示调休上班, 为holiday或者缺省表示放假
+#[derive(Debug, Deserialize, Serialize)]
+struct HolidayRow {
+    #[serde(rename = "_date")]
+    date: MyDateType,
+    #[serde(rename = "_name")]
+    name: String,
+    #[serde(rename = "_kind", default)]
+    kind: String,
+}
+
+/// 节假日及调休上班日, 从扩展的holidays.csv加载, 用于判断DayKind
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HolidayTable {
+    /// 放假的日期(仅周一至周五) => 名称
+    holidays: BTreeMap<MyDateType, String>,
+    /// 调休上班的日期 => 对应节日的名称
+    makeup_days: BTreeMap<MyDateType, String>,
+}
+
+impl HolidayTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加放假日期, 周六周日会被忽略
+    pub fn add_holiday(&mut self, date: MyDateType, name: &str) {
+        if !is_weekend(&date) {
+            self.makeup_days.remove(&date);
+            self.holidays.insert(date, name.to_string());
+        }
+    }
+
+    /// 添加调休上班日期
+    pub fn add_makeup_day(&mut self, date: MyDateType, name: &str) {
+        self.holidays.remove(&date);
+        self.makeup_days.insert(date, name.to_string());
+    }
+
+    /// 合并other的数据, 同一日期以other为准
+    pub fn merge(&mut self, other: &HolidayTable) {
+        for (date, name) in other.holidays.iter() {
+            self.add_holiday(*date, name);
+        }
+        for (date, name) in other.makeup_days.iter() {
+            self.add_makeup_day(*date, name);
+        }
+    }
+
+    /// 按日期排序的节假日
+    pub fn holidays(&self) -> Vec<Holiday> {
+        self.holidays
+            .iter()
+            .map(|(date, name)| Holiday {
+                date: *date,
+                name: name.clone(),
+            })
+            .collect()
+    }
+
+    /// year年按日期排序的节假日
+    pub fn holidays_in(&self, year: i32) -> Vec<Holiday> {
+        self.holidays
+            .range(make_date(year, 1, 1)..=make_date(year, 12, 31))
+            .map(|(date, name)| Holiday {
+                date: *date,
+                name: name.clone(),
+            })
+            .collect()
+    }
+
+    /// date是节假日时返回名称, 周末返回None
+    pub fn holiday_at(&self, date: &MyDateType) -> Option<&str> {
+        self.holidays.get(date).map(|name| name.as_str())
+    }
+
+    /// date所在的连续休息日(节假日及相连的周末, 不含调休上班日), 不含节假日的普通周末返回None
+    pub fn holiday_block(&self, date: &MyDateType) -> Option<HolidayBlock> {
+        find_holiday_block(
+            date,
+            |date| !self.day_kind(date).is_working_day(),
+            |date| self.holiday_at(date).map(|name| name.to_string()),
+        )
+    }
+
+    /// 按日期排序的调休上班日
+    pub fn makeup_days(&self) -> Vec<MyDateType> {
+        self.makeup_days.keys().copied().collect()
+    }
+
+    /// 有数据的年份, 按升序
+    pub fn years(&self) -> Vec<i32> {
+        let mut years: Vec<i32> = self
+            .holidays
+            .keys()
+            .chain(self.makeup_days.keys())
+            .map(|date| date_ymd(date).0)
+            .collect();
+        years.sort();
+        years.dedup();
+        years
+    }
+
+    /// 是否包含year年的数据
+    pub fn contains_year(&self, year: i32) -> bool {
+        let (first, last) = (make_date(year, 1, 1), make_date(year, 12, 31));
+        self.holidays.range(first..=last).next().is_some()
+            || self.makeup_days.range(first..=last).next().is_some()
+    }
+
+    /// 日期的类别, 没有数据的年份也按周一至周五为工作日处理, 参见contains_year()
+    pub fn day_kind(&self, date: &MyDateType) -> DayKind {
+        if let Some(name) = self.holidays.get(date) {
+            DayKind::Holiday { name: name.clone() }
+        } else if self.makeup_days.contains_key(date) {
+            DayKind::MakeupWorkday
+        } else if is_weekend(date) {
+            DayKind::Weekend
+        } else {
+            DayKind::Weekday
+        }
+    }
+
+    pub fn load_csv_file<P: AsRef<Path>>(path: P) -> Result<HolidayTable> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| path.display().to_string())?;
+        Self::load_csv_read(DecodeReaderBytes::new(file))
+            .with_context(|| path.display().to_string())
+    }
+
+    /// 可以直接从字符串加载, str.as_bytes(), 兼容没有_kind列的holidays.csv
+    pub fn load_csv_read<R: std::io::Read>(read: R) -> Result<HolidayTable> {
+        let mut table = HolidayTable::new();
+        for result in Reader::from_reader(read).deserialize::<HolidayRow>() {
+            let row = result?;
+            match row.kind.trim() {
+                "" | "holiday" => table.add_holiday(row.date, &row.name),
+                "makeup" => table.add_makeup_day(row.date, &row.name),
+                other => return Err(anyhow!("unknown _kind `{}` of {}", other, row.date)),
+            }
+        }
+        Ok(table)
+    }
+
+    /// 按日期顺序写出扩展的holidays.csv
+    pub fn write_csv<W: std::io::Write>(&self, write: W) -> Result<()> {
+        let holidays = self
+            .holidays
+            .iter()
+            .map(|(date, name)| (date, name, "holiday"));
+        let makeup_days = self
+            .makeup_days
+            .iter()
+            .map(|(date, name)| (date, name, "makeup"));
+        let mut rows: Vec<_> = holidays.chain(makeup_days).collect();
+        rows.sort_by_key(|(date, _, _)| **date);
+
+        let mut wtr = Writer::from_writer(write);
+        for (date, name, kind) in rows {
+            wtr.serialize(HolidayRow {
+                date: *date,
+                name: name.clone(),
+                kind: kind.to_string(),
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+pub(crate) fn add_days(date: &MyDateType, days: i32) -> MyDateType {
+    date_from_days_since_epoch(date_to_days_since_epoch(date) + days)
+}
+
+/// 星期几, 0表示周一, 6表示周日
+pub(crate) fn weekday_index(date: &MyDateType) -> i32 {
+    // 1970-01-01是周四
+    (date_to_days_since_epoch(date) + 3).rem_euclid(7)
+}
+
+/// 是否周六周日
+pub fn is_weekend(date: &MyDateType) -> bool {
+    weekday_index(date) >= 5
+}
+
+/// 农历year年month月day日对应的公历日期, leap为true表示闰月, 仅支持2000~2100年
+pub fn lunar_to_solar(year: i32, month: u32, day: u32, leap: bool) -> Result<MyDateType> {
+    let info = year
+        .checked_sub(LUNAR_FIRST_YEAR)
+        .and_then(|idx| LUNAR_INFO.get(idx as usize))
+        .ok_or_else(|| anyhow!("lunar year {} out of range", year))?;
+    let month_days = |m: u32| if info & (1 << (m - 1)) != 0 { 30 } else { 29 };
+    let leap_month = (info >> 12) & 0xf;
+    let leap_days = if info & (1 << 16) != 0 { 30 } else { 29 };
+    if !(1..=12).contains(&month) || (leap && leap_month != month) {
+        return Err(anyhow!(
+            "invalid lunar month {}{} of {}",
+            if leap { "闰" } else { "" },
+            month,
+            year
+        ));
+    }
+    let max_day = if leap { leap_days } else { month_days(month) };
+    if day < 1 || day > max_day {
+        return Err(anyhow!(
+            "invalid lunar day {} of month {}, {}",
+            day,
+            month,
+            year
+        ));
+    }
+
+    let mut offset = (info >> 17) as i32;
+    for m in 1..month {
+        offset += month_days(m) as i32;
+        if m == leap_month {
+            offset += leap_days as i32;
+        }
+    }
+    if leap {
+        offset += month_days(month) as i32;
+    }
+    Ok(add_days(&make_date(year, 1, 21), offset + day as i32 - 1))
+}
+
+/// 清明(节气)的公历日期, 使用寿星公式, 仅支持2000~2099年
+pub fn qingming(year: i32) -> Result<MyDateType> {
+    if !(2000..=2099).contains(&year) {
+        return Err(anyhow!("qingming of {} out of range", year));
+    }
+    let y = (year % 100) as f64;
+    let day = (y * 0.2422 + 4.81).floor() - (y / 4.0).floor();
+    Ok(make_date(year, 4, day as u32))
+}
+
+/// 按2025年起施行的放假办法, 预测year年的节假日(仅周一至周五), 按日期排序
+///
+/// 春节: 除夕至初七(共8天, 与2025、2026年公布的安排一致); 劳动节: 5月1日至5日; 国庆: 10月1日至7日, 中秋在9月29日~10月8日之间时合并为8天;
+/// 元旦、清明、端午、中秋: 当天, 逢周二则连同周一, 逢周四则连同周五, 逢周末则补周一.
+///
+/// 结果是估算的, 仅在国务院尚未公布(或者日历尚未更新)时使用
+pub fn predict_holidays(year: i32) -> Result<Vec<Holiday>> {
+    let mut days: Vec<(MyDateType, &str)> = Vec::with_capacity(32);
+    let mut single = |date: MyDateType, name| {
+        match weekday_index(&date) {
+            1 => days.extend([(add_days(&date, -1), name), (date, name)]),
+            3 => days.extend([(date, name), (add_days(&date, 1), name)]),
+            w if w >= 5 => days.push((add_days(&date, 7 - w), name)),
+            _ => days.push((date, name)),
+        };
+    };
+    single(make_date(year, 1, 1), "元旦");
+    single(qingming(year)?, "清明");
+    single(lunar_to_solar(year, 5, 5, false)?, "端午");
+    let mid_autumn = lunar_to_solar(year, 8, 15, false)?;
+    let national_day = make_date(year, 10, 1);
+    let merged = mid_autumn >= make_date(year, 9, 29) && mid_autumn <= make_date(year, 10, 8);
+    if !merged {
+        single(mid_autumn, "中秋");
+    }
+
+    let new_year = lunar_to_solar(year, 1, 1, false)?;
+    days.push((add_days(&new_year, -1), "除夕"));
+    days.extend((0..7).map(|i| (add_days(&new_year, i), "春节")));
+    days.extend((1..=5).map(|d| (make_date(year, 5, d), "劳动节")));
+    if merged {
+        let first = mid_autumn.min(national_day);
+        days.extend((0..8).map(|i| {
+            let date = add_days(&first, i);
+            (
+                date,
+                if date == mid_autumn {
+                    "中秋"
+                } else {
+                    "国庆"
+                },
+            )
+        }));
+    } else {
+        days.extend((0..7).map(|i| (add_days(&national_day, i), "国庆")));
+    }
+
+    let first_day = make_date(year, 1, 1);
+    let last_day = make_date(year, 12, 31);
+    let mut result: Vec<Holiday> = Vec::with_capacity(days.len());
+    days.sort_by_key(|(date, _)| *date);
+    for (date, name) in days {
+        // 除夕可能在上一年, 补休的周一可能与其他假期重叠
+        if is_weekend(&date) || date < first_day || date > last_day {
+            continue;
+        }
+        if result.last().is_some_and(|h| h.date == date) {
+            continue;
+        }
+        result.push(Holiday {
+            date,
+            name: name.to_string(),
+        });
+    }
+    Ok(result)
+}
+
+/// 将当年假期列表，转换为交易日列表(排除了周末及这些假期, 仅交易日)
+pub fn holidays_to_tradingdays(holiday_list: &[MyDateType]) -> Result<Vec<MyDateType>> {
+    let first = holiday_list
+        .first()
+        .ok_or_else(|| anyhow!("holidays_to_tradingdays(): 假期列表为空"))?;
+    let year = date_ymd(first).0;
+    let mut the_day = make_date(year, 1, 1);
+    let next_year_first = make_date(year + 1, 1, 1);
+    let mut result: Vec<MyDateType> = Vec::with_capacity(260);
+    while the_day < next_year_first {
+        if !is_weekend(&the_day) && !holiday_list.contains(&the_day) {
+            result.push(the_day);
+        }
+        the_day = tomorrow(&the_day);
+    }
+    log::debug!("{}年度交易日数量({})", year, result.len());
+    Ok(result)
+}
+
+/// 将交易日列表转换为Tradingday列表，交易日列表可以来自holidays_to_tradingdays()函数转换，也可以来自从其他平台的查询
+/// 更新: 现在非交易日也写入数据库，Tradingday的trading项为false，周六非交易日，但周五夜盘会持续到凌晨，所以其morning项可以为true
+/// 返回值: 上年度最后一个交易日及本年度所有日期构成的Tradingday列表
+pub fn tradingdays_to_calendar(trading_days: &[MyDateType]) -> Result<Vec<Tradingday>> {
+    if trading_days.len() <= 2 {
+        return Err(anyhow!(
+            "tradingdays_to_calendar() 输入日期数据太短({})",
+            trading_days.len()
+        ));
+    }
+
+    // 去年计算上一年度的最后一个交易日的next时，是估算的，并不准确，因为那时国家尚未公布本新一年度的节假日，
+    // 即我们并不知道后一年元旦放假的具体情况
+    // 此时假期安排已经公布，需要进行修正
+    // pre_day是上一年度的最后一个交易日，很可能是12月31日，如果不是的话，则倒退寻找
+    let mut pre_day = yesterday(&make_date(date_ymd(&trading_days[0]).0, 1, 1));
+    while is_weekend(&pre_day) {
+        pre_day = yesterday(&pre_day);
+    }
+
+    // pre_day_night: 表示pre_day是否有夜盘交易；后面会放元旦假，此时一定没有夜盘
+    let mut pre_day_night = false;
+    let first_tday = trading_days[0];
+    let length = trading_days.len();
+    let mut result: Vec<Tradingday> = Vec::with_capacity(length + 130);
+
+    // 补充上年最后一个交易日的更新数据，
+    // 注意: 如果要写数据库的话，这条记录必须是更新，而不是插入
+    // 由于中国在十一月十二月没有额外的公共假期，所以很容易判断pre_day的morning，如果是周一则没有，其他日期则有
+    result.push(Tradingday {
+        date: pre_day,
+        morning: weekday_index(&pre_day) != 0,
+        trading: true,
+        night: pre_day_night,
+        next: first_tday,
+    });
+
+    let next_yuandan = make_date(date_ymd(&trading_days[length - 1]).0 + 1, 1, 1);
+    log::debug!(
+        "正在准备数据, [{}, {}),请稍候... ",
+        first_tday,
+        next_yuandan
+    );
+
+    for (idx, the_day) in trading_days.iter().enumerate() {
+        let the_day = *the_day;
+
+        // 中间可能有非交易日
+        while pre_day < yesterday(&the_day) {
+            pre_day = tomorrow(&pre_day);
+            result.push(Tradingday {
+                date: pre_day,
+                morning: pre_day_night,
+                trading: false,
+                night: false,
+                next: the_day,
+            });
+            pre_day_night = false
+        }
+
+        if idx == length - 1 {
+            // 这是当年度的最后一个交易日天(不一定是12月31日，因为12月31日有可能是周末)，不能通过idx+1获取更后面一天next_tday,
+            // 从the_day(交易)开始，到后一年元旦，中间都不交易，而且，元旦也不交易, the_day没有夜盘
+            let mut next_year_first_trading_day = next_working_day(&the_day, 1);
+            if date_ymd(&next_year_first_trading_day).2 == 1 {
+                // 如果元旦不是周末，向后再找
+                next_year_first_trading_day = next_working_day(&next_year_first_trading_day, 1);
+            }
+            result.push(Tradingday {
+                date: the_day,
+                morning: pre_day_night,
+                trading: true,
+                night: false,
+                next: next_year_first_trading_day,
+            });
+            let mut the_day = tomorrow(&the_day);
+            while the_day < next_yuandan {
+                result.push(Tradingday {
+                    date: the_day,
+                    morning: false,
+                    trading: false,
+                    night: false,
+                    next: next_year_first_trading_day,
+                });
+                the_day = tomorrow(&the_day);
+            }
+        } else {
+            let next_trading_day = trading_days[idx + 1];
+            // 判断当天凌晨有交易：昨天夜里有交易的话，则当天凌晨有交易，反之亦然，充要条件
+            // 判断当天是否有夜盘：第二天是交易日，或者3天后是交易日且为星期一
+            // 这个判断可靠吗？ 有没有可能，仅放假周六周日，但周五晚上没有夜盘的情况？
+            let has_night_mkt = next_trading_day == tomorrow(&the_day)
+                || (next_trading_day == add_days(&the_day, 3)
+                    && weekday_index(&next_trading_day) == 0);
+            result.push(Tradingday {
+                date: the_day,
+                morning: pre_day_night,
+                trading: true,
+                night: has_night_mkt,
+                next: next_trading_day,
+            });
+            pre_day = the_day;
+            pre_day_night = has_night_mkt;
+        }
+    }
+    log::debug!("自然日数量({}),含上年最后交易日", result.len());
+    Ok(result)
+}
+
+/// 后一交易日是次日(或者周五之后的周一), 只有这样的交易日才可能有夜盘
+fn is_next_adjacent(day: &Tradingday) -> bool {
+    day.next == tomorrow(&day.date)
+        || (day.next == add_days(&day.date, 3) && weekday_index(&day.next) == 0)
+}
+
+/// list中每一天按常规是否有夜盘, 用于节前最后一天等没有夜盘的日子恢复相邻交易日之后, 重新确定夜盘
+///
+/// 后一交易日相邻的交易日取其night; 其他日子取前后最近的这样的交易日, 任一有夜盘即为有
+pub fn regular_nights(list: &[Tradingday]) -> Vec<bool> {
+    let ordinary = |day: &Tradingday| (day.trading && is_next_adjacent(day)).then_some(day.night);
+    let mut prev: Vec<Option<bool>> = Vec::with_capacity(list.len());
+    let mut last = None;
+    for day in list.iter() {
+        last = ordinary(day).or(last);
+        prev.push(last);
+    }
+    let mut result = vec![false; list.len()];
+    let mut last = None;
+    for (idx, day) in list.iter().enumerate().rev() {
+        result[idx] = match ordinary(day) {
+            Some(night) => night,
+            None => prev[idx].unwrap_or(false) || last.unwrap_or(false),
+        };
+        last = ordinary(day).or(last);
+    }
+    result
+}
+
+/// 修改了某些日期的trading之后(如台风停市、日历合并), 重新计算next, 以及受影响的night和morning
+///
+/// 修改之前的常规夜盘由list本身按regular_nights()推断, 参见relink_tradingdays_with()
+pub fn relink_tradingdays(list: &mut [Tradingday]) {
+    let regular = regular_nights(list);
+    relink_tradingdays_with(list, &regular);
+}
+
+/// 重新计算next, 以及受影响的night和morning, regular为每一天按常规是否有夜盘, 参见regular_nights()
+///
+/// list必须按日期排序且连续; 最后一个交易日之后的日期无法确定next, 保持不变;
+/// 后一交易日不是次日(或者周五之后的周一)时, 当天没有夜盘; 重新相邻的交易日(如临时开市的前一天)按regular恢复夜盘;
+/// morning由前一自然日的night决定
+pub fn relink_tradingdays_with(list: &mut [Tradingday], regular: &[bool]) {
+    let mut next_trading: Option<MyDateType> = None;
+    for day in list.iter_mut().rev() {
+        if let Some(next) = next_trading {
+            day.next = next;
+        }
+        if day.trading {
+            next_trading = Some(day.date);
+        }
+    }
+    for idx in 0..list.len() {
+        let day = &mut list[idx];
+        let regular_night = regular.get(idx).copied().unwrap_or(false);
+        day.night = (day.night || regular_night) && day.trading && is_next_adjacent(day);
+        if idx > 0 && list[idx - 1].date == yesterday(&list[idx].date) {
+            list[idx].morning = list[idx - 1].night;
+        }
+    }
+}
+
+/// 银行间市场(CFETS)的Tradingday列表(含上年最后交易日), 节假日与交易所相同, 但调休上班日也交易, 没有夜盘
+///
+/// holidays及makeup_days应为同一年的数据, 参见tradingdays_to_calendar()
+pub fn cfets_tradingdays_to_calendar(
+    holidays: &[MyDateType],
+    makeup_days: &[MyDateType],
+) -> Result<Vec<Tradingday>> {
+    let mut trading_days = holidays_to_tradingdays(holidays)?;
+    trading_days.extend_from_slice(makeup_days);
+    trading_days.sort();
+    trading_days.dedup();
+    let mut result = tradingdays_to_calendar(&trading_days)?;
+    for day in result.iter_mut() {
+        day.morning = false;
+        day.night = false;
+    }
+    Ok(result)
+}
+
+/// 根据predict_holidays()估算year年的Tradingday列表(含上年最后交易日),
+/// 结果是估算的, 仅用于日历数据缺失时, 参见TradeCalendar::time_changed()的fail_safe
+pub fn estimate_tradingdays(year: i32) -> Result<Vec<Tradingday>> {
+    let holidays: Vec<MyDateType> = predict_holidays(year)?
+        .into_iter()
+        .map(|h| h.date)
+        .collect();
+    let trading_days = holidays_to_tradingdays(&holidays)?;
+    tradingdays_to_calendar(&trading_days)
+}
@@ -10,6 +10,21 @@ mod tm_jiff;
 #[cfg(feature = "with-jiff")]
 pub use tm_jiff::*;
 
+/// 日期时间加上secs秒, secs可以为负数
+pub fn datetime_add_secs(datetime: &MyDateTimeType, secs: i64) -> MyDateTimeType {
+    datetime_from_timestamp_nanos(datetime_to_timestamp_nanos(datetime) + secs * 1_000_000_000)
+}
+
+/// UTC时间戳(纳秒)转换为UTC偏移为utc_offset_secs秒的本地时间
+pub fn utc_nanos_to_local(nanos: i64, utc_offset_secs: i32) -> MyDateTimeType {
+    datetime_from_timestamp_nanos(nanos + utc_offset_secs as i64 * 1_000_000_000)
+}
+
+/// 两个日期时间相差的秒数(end - start), 不足一秒的部分被舍去
+pub fn datetime_diff_secs(start: &MyDateTimeType, end: &MyDateTimeType) -> i64 {
+    (datetime_to_timestamp_nanos(end) - datetime_to_timestamp_nanos(start)) / 1_000_000_000
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,14 +1,21 @@
 // use anyhow::{anyhow, Result};
-use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
 
 pub type MyDateType = NaiveDate;
 pub type MyDateTimeType = NaiveDateTime;
 pub type MyTimeType = NaiveTime;
+/// 带时区的日期时间, 其他时区的DateTime<Tz>可以用fixed_offset()转换
+pub type MyZonedType = DateTime<FixedOffset>;
 
 pub fn make_date(year: i32, month: u32, day: u32) -> MyDateType {
     return NaiveDate::from_ymd_opt(year, month, day).expect("from_ymd_opt() failed");
 }
 
+/// 日期的(年, 月, 日)
+pub fn date_ymd(date: &MyDateType) -> (i32, u32, u32) {
+    (date.year(), date.month(), date.day())
+}
+
 pub fn make_time(hour: u32, min: u32, sec: u32) -> MyTimeType {
     return NaiveTime::from_hms_opt(hour, min, sec).expect("from_hms_opt() failed");
 }
@@ -27,11 +34,28 @@ pub fn date_at_hms(date: &MyDateType, hour: u32, min: u32, sec: u32) -> MyDateTi
         .expect("and_hms_opt() failed");
 }
 
+pub fn date_at_time(date: &MyDateType, time: &MyTimeType) -> MyDateTimeType {
+    date.and_time(*time)
+}
+
 pub fn get_now() -> MyDateTimeType {
     use chrono::Local;
     Local::now().naive_local()
 }
 
+/// 当前UTC时间, 从1970-01-01 00:00:00 UTC以来的纳秒总数
+pub fn now_utc_nanos() -> i64 {
+    Utc::now()
+        .timestamp_nanos_opt()
+        .expect("timestamp_nanos_opt() failed")
+}
+
+/// 带时区的日期时间转换为UTC偏移为utc_offset_secs秒的本地时间
+pub fn zoned_to_local(input: &MyZonedType, utc_offset_secs: i32) -> MyDateTimeType {
+    let offset = FixedOffset::east_opt(utc_offset_secs).expect("invalid utc offset");
+    input.with_timezone(&offset).naive_local()
+}
+
 /// 从1970-01-01开始的天数构造日期
 pub fn date_from_days_since_epoch(days_since_epoch: i32) -> MyDateType {
     // 1970年1月1日是公元1年之后的第719,163天。
@@ -6,12 +6,20 @@ pub type MyDateTimeType = DateTime;
 
 pub type MyTimeType = Time;
 
+/// 带时区的日期时间
+pub type MyZonedType = jiff::Zoned;
+
 //////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub fn make_date(year: i32, month: u32, day: u32) -> MyDateType {
     return Date::constant(year as i16, month as i8, day as i8);
 }
 
+/// 日期的(年, 月, 日)
+pub fn date_ymd(date: &MyDateType) -> (i32, u32, u32) {
+    (date.year() as i32, date.month() as u32, date.day() as u32)
+}
+
 pub fn make_time(hour: u32, minute: u32, second: u32) -> MyTimeType {
     return Time::constant(hour as i8, minute as i8, second as i8, 0);
 }
@@ -28,11 +36,28 @@ pub fn date_at_hms(date: &MyDateType, hour: u32, minute: u32, second: u32) -> My
     return date.at(hour as i8, minute as i8, second as i8, 0);
 }
 
+pub fn date_at_time(date: &MyDateType, time: &MyTimeType) -> MyDateTimeType {
+    date.to_datetime(*time)
+}
+
 pub fn get_now() -> MyDateTimeType {
     use jiff::Zoned;
     Zoned::now().datetime()
 }
 
+/// 当前UTC时间, 从1970-01-01 00:00:00 UTC以来的纳秒总数
+pub fn now_utc_nanos() -> i64 {
+    jiff::Timestamp::now().as_nanosecond() as i64
+}
+
+/// 带时区的日期时间转换为UTC偏移为utc_offset_secs秒的本地时间
+pub fn zoned_to_local(input: &MyZonedType, utc_offset_secs: i32) -> MyDateTimeType {
+    let offset = jiff::tz::Offset::from_seconds(utc_offset_secs).expect("invalid utc offset");
+    input
+        .with_time_zone(jiff::tz::TimeZone::fixed(offset))
+        .datetime()
+}
+
 //////////////////////////////////////////////////////////////////////////////////////////////
 
 /// 从1970-01-01开始的天数构造日期
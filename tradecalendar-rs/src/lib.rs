@@ -1,17 +1,35 @@
+mod backtest;
+mod calendar_clock;
+mod clock;
+mod combine;
 mod db_clickhouse;
 mod db_odbc;
 mod db_sqlx;
+mod exchange;
+mod hkex;
+mod holiday;
 pub mod jcswitch;
+mod overrides;
+mod session;
 mod tests;
 mod tradecalendar;
 
 use anyhow::{Result, anyhow};
 use std::path::Path;
 
+pub use backtest::*;
+pub use calendar_clock::*;
+pub use clock::*;
+pub use combine::*;
 pub use db_clickhouse::{load_tradingdays_from_clickhouse, load_tradingdays_from_clickhouse_async};
 pub use db_odbc::load_tradingdays_from_odbc;
 pub use db_sqlx::*;
+pub use exchange::*;
+pub use hkex::*;
+pub use holiday::*;
 use jcswitch::{MyDateType, get_now};
+pub use overrides::*;
+pub use session::*;
 
 pub use tradecalendar::*;
 
@@ -59,12 +77,44 @@ pub fn load_tradingdays_buildin() -> Result<Vec<Tradingday>> {
     Tradingday::load_csv_read(csv_str.as_bytes())
 }
 
+/// 使用内置的holidays_history.csv(往年)及holidays.csv(当年)加载节假日及调休上班日
+pub fn load_holidays_buildin() -> Result<HolidayTable> {
+    let csv_str = include_str!("../../holidays_history.csv");
+    let mut table = HolidayTable::load_csv_read(csv_str.as_bytes())?;
+    let csv_str = include_str!("../../holidays.csv");
+    table.merge(&HolidayTable::load_csv_read(csv_str.as_bytes())?);
+    Ok(table)
+}
+
+/// 使用内置的csv文件加载某个交易所的交易日列表
+pub fn load_exchange_tradingdays_buildin(exchange: Exchange) -> Result<Vec<Tradingday>> {
+    let full_list = load_tradingdays_buildin()?;
+    match exchange {
+        Exchange::CFETS => tradingdays_for_cfets(&full_list, &load_holidays_buildin()?),
+        Exchange::HKEX => match (full_list.first(), full_list.last()) {
+            (Some(first), Some(last)) => tradingdays_for_hkex(&first.date, &last.date),
+            _ => Err(anyhow!("buildin tradingday list is empty")),
+        },
+        _ => Ok(tradingdays_for_exchange(&full_list, exchange)),
+    }
+}
+
+/// 使用内置的csv文件构造各交易所的日历, 可以指定开始日期
+pub fn get_buildin_registry(start_date: Option<MyDateType>) -> Result<CalendarRegistry> {
+    let mut full_list = load_tradingdays_buildin()?;
+    drain_tday_list(&mut full_list, start_date);
+    let mut registry = CalendarRegistry::from_tradingdays(&full_list)?;
+    registry.insert_cfets(&full_list, &load_holidays_buildin()?)?;
+    Ok(registry)
+}
+
 /// 使用内置的csv文件构造交易日历, 可以指定开始日期，因为很多时候不用从2009年那么早开始
 pub fn get_buildin_calendar(start_date: Option<MyDateType>) -> Result<TradeCalendar> {
     let mut full_list = load_tradingdays_buildin()?;
     drain_tday_list(&mut full_list, start_date);
     let mut calendar = TradeCalendar::new();
     calendar.reload(full_list)?;
+    calendar.set_holiday_table(Some(load_holidays_buildin()?));
     return Ok(calendar);
 }
 
@@ -0,0 +1,193 @@
+use anyhow::{Result, anyhow};
+use csv::{Reader, Writer};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::holiday::{regular_nights, relink_tradingdays_with};
+use crate::jcswitch::*;
+use crate::tradecalendar::{Tradingday, search_days};
+
+/// 对某一天的临时调整
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverrideKind {
+    /// 全天休市, 如2020年春节延长休市
+    Close,
+    /// 临时开市
+    Open,
+    /// 取消当天的夜盘, 如系统升级
+    CancelNight,
+    /// 增加当天的夜盘, 后一交易日不相邻时无效
+    AddNight,
+}
+
+impl OverrideKind {
+    /// csv中_kind列的取值
+    pub fn code(&self) -> &'static str {
+        match self {
+            OverrideKind::Close => "close",
+            OverrideKind::Open => "open",
+            OverrideKind::CancelNight => "cancel_night",
+            OverrideKind::AddNight => "add_night",
+        }
+    }
+}
+
+impl Display for OverrideKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for OverrideKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "close" => Ok(OverrideKind::Close),
+            "open" => Ok(OverrideKind::Open),
+            "cancel_night" => Ok(OverrideKind::CancelNight),
+            "add_night" => Ok(OverrideKind::AddNight),
+            _ => Err(anyhow!("unknown override kind `{}`", s)),
+        }
+    }
+}
+
+/// 一条调整记录, reason用于审计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarOverride {
+    pub date: MyDateType,
+    pub kind: OverrideKind,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct OverrideRow {
+    #[serde(rename = "_date")]
+    date: MyDateType,
+    #[serde(rename = "_kind")]
+    kind: String,
+    #[serde(rename = "_reason", default)]
+    reason: String,
+}
+
+/// 叠加在Tradingday列表上的临时调整(停市、取消夜盘等), 不需要修改calendar.csv
+///
+/// csv格式为`_date,_kind,_reason`, _kind参见OverrideKind::code()
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CalendarOverrides {
+    items: Vec<CalendarOverride>,
+}
+
+impl CalendarOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一条调整, 同一天的多条调整按添加顺序生效
+    pub fn add(&mut self, date: MyDateType, kind: OverrideKind, reason: &str) -> &mut Self {
+        self.items.push(CalendarOverride {
+            date,
+            kind,
+            reason: reason.to_string(),
+        });
+        self
+    }
+
+    pub fn close_day(&mut self, date: MyDateType, reason: &str) -> &mut Self {
+        self.add(date, OverrideKind::Close, reason)
+    }
+
+    pub fn open_day(&mut self, date: MyDateType, reason: &str) -> &mut Self {
+        self.add(date, OverrideKind::Open, reason)
+    }
+
+    pub fn cancel_night(&mut self, date: MyDateType, reason: &str) -> &mut Self {
+        self.add(date, OverrideKind::CancelNight, reason)
+    }
+
+    pub fn add_night(&mut self, date: MyDateType, reason: &str) -> &mut Self {
+        self.add(date, OverrideKind::AddNight, reason)
+    }
+
+    /// 全部调整, 按添加顺序
+    pub fn items(&self) -> &[CalendarOverride] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 某一天的调整记录
+    pub fn overrides_at(&self, date: &MyDateType) -> Vec<&CalendarOverride> {
+        self.items.iter().filter(|o| &o.date == date).collect()
+    }
+
+    /// 按日期顺序应用到list, 并重新计算next、night、morning, 参见relink_tradingdays_with()
+    ///
+    /// 临时开市使前一交易日的后一交易日重新相邻时, 按调整之前的常规夜盘恢复夜盘, 参见regular_nights();
+    /// 不在list范围内的日期将被忽略, 返回实际生效的记录, 按日期排序
+    pub fn apply(&self, list: &mut [Tradingday]) -> Vec<CalendarOverride> {
+        let mut regular = regular_nights(list);
+        let mut items: Vec<&CalendarOverride> = self.items.iter().collect();
+        items.sort_by_key(|o| o.date);
+        let mut applied = Vec::new();
+        for item in items {
+            let (_, index, _) = search_days(list, &item.date);
+            if index < 0 {
+                continue;
+            }
+            let index = index as usize;
+            let day = &mut list[index];
+            match item.kind {
+                OverrideKind::Close => {
+                    day.trading = false;
+                    day.night = false;
+                }
+                OverrideKind::Open => day.trading = true,
+                OverrideKind::CancelNight => {
+                    day.night = false;
+                    regular[index] = false;
+                }
+                OverrideKind::AddNight => day.night = day.trading,
+            }
+            applied.push(item.clone());
+        }
+        relink_tradingdays_with(list, &regular);
+        applied
+    }
+
+    pub fn load_csv_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::load_csv_read(file)
+    }
+
+    pub fn load_csv_read<R: std::io::Read>(read: R) -> Result<Self> {
+        let mut overrides = Self::new();
+        for result in Reader::from_reader(read).deserialize::<OverrideRow>() {
+            let row = result?;
+            let kind: OverrideKind = row
+                .kind
+                .parse()
+                .map_err(|e| anyhow!("{} of {}", e, row.date))?;
+            overrides.add(row.date, kind, &row.reason);
+        }
+        Ok(overrides)
+    }
+
+    /// 按添加顺序写出csv
+    pub fn write_csv<W: std::io::Write>(&self, write: W) -> Result<()> {
+        let mut wtr = Writer::from_writer(write);
+        for item in self.items.iter() {
+            wtr.serialize(OverrideRow {
+                date: item.date,
+                kind: item.kind.code().to_string(),
+                reason: item.reason.clone(),
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,687 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::exchange::Exchange;
+use crate::jcswitch::*;
+use crate::tradecalendar::{NotTradingSearchMethod, TradingdayCache};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 24 * 3600;
+
+/// 时间距离当天0点的秒数
+pub(crate) fn secs_from_midnight(time: &MyTimeType) -> i64 {
+    time_to_midnight_nanos(time) / NANOS_PER_SECOND
+}
+
+/// 交易时段属于交易日的哪一部分, 与Tradingday的night/morning/trading对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum SessionPart {
+    /// 夜盘, 前一交易日夜里21:00~24:00, 对应Tradingday::night
+    Night,
+    /// 凌晨盘, 夜盘跨越0点之后的部分, 对应Tradingday::morning
+    Morning,
+    /// 日盘, 对应Tradingday::trading
+    Day,
+}
+
+/// 一个连续的交易区间[begin, end)
+///
+/// 夜盘区间的end为00:00时表示24:00
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct SessionInterval {
+    pub part: SessionPart,
+    pub begin: MyTimeType,
+    pub end: MyTimeType,
+}
+
+impl SessionInterval {
+    pub fn new(part: SessionPart, begin: MyTimeType, end: MyTimeType) -> Self {
+        Self { part, begin, end }
+    }
+
+    /// 开始时间距离0点的秒数
+    pub fn begin_secs(&self) -> i64 {
+        secs_from_midnight(&self.begin)
+    }
+
+    /// 结束时间距离0点的秒数, 夜盘在24:00结束时为86400
+    pub fn end_secs(&self) -> i64 {
+        let secs = secs_from_midnight(&self.end);
+        if secs == 0 && self.part == SessionPart::Night {
+            SECONDS_PER_DAY
+        } else {
+            secs
+        }
+    }
+
+    /// 时长(秒)
+    pub fn duration_secs(&self) -> i64 {
+        self.end_secs() - self.begin_secs()
+    }
+
+    /// time是否在[begin, end)之内
+    pub fn contains(&self, time: &MyTimeType) -> bool {
+        let secs = secs_from_midnight(time);
+        secs >= self.begin_secs() && secs < self.end_secs()
+    }
+}
+
+impl Display for SessionInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} {}~{}",
+            self.part,
+            self.begin.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// 品种的交易时段模板, 由若干按时间顺序排列的区间构成: 夜盘, 凌晨盘, 日盘
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SessionTemplate {
+    pub name: String,
+    pub intervals: Vec<SessionInterval>,
+}
+
+impl Display for SessionTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.name)?;
+        for interval in self.intervals.iter() {
+            write!(f, " [{}]", interval)?;
+        }
+        Ok(())
+    }
+}
+
+impl SessionTemplate {
+    /// intervals必须按 夜盘->凌晨盘->日盘 的顺序排列, 且互不重叠
+    pub fn new(name: &str, intervals: Vec<SessionInterval>) -> Result<Self> {
+        let order = |part: SessionPart| match part {
+            SessionPart::Night => 0,
+            SessionPart::Morning => 1,
+            SessionPart::Day => 2,
+        };
+        for interval in intervals.iter() {
+            if interval.end_secs() <= interval.begin_secs() {
+                return Err(anyhow!("SessionTemplate `{}`: invalid {}", name, interval));
+            }
+        }
+        for pair in intervals.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let ordered = match order(a.part).cmp(&order(b.part)) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Equal => a.end_secs() <= b.begin_secs(),
+                std::cmp::Ordering::Greater => false,
+            };
+            if !ordered {
+                return Err(anyhow!(
+                    "SessionTemplate `{}`: [{}] and [{}] out of order",
+                    name,
+                    a,
+                    b
+                ));
+            }
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            intervals,
+        })
+    }
+
+    /// 商品期货: 日盘 9:00~10:15, 10:30~11:30, 13:30~15:00
+    ///
+    /// night_end: 夜盘结束时间, 如23:00, 1:00, 2:30, None表示没有夜盘
+    pub fn commodity(night_end: Option<MyTimeType>) -> Self {
+        let mut intervals = Vec::with_capacity(5);
+        if let Some(end) = night_end {
+            let night_begin = make_time(21, 0, 0);
+            if end > night_begin {
+                intervals.push(SessionInterval::new(SessionPart::Night, night_begin, end));
+            } else {
+                let midnight = make_time(0, 0, 0);
+                intervals.push(SessionInterval::new(
+                    SessionPart::Night,
+                    night_begin,
+                    midnight,
+                ));
+                intervals.push(SessionInterval::new(SessionPart::Morning, midnight, end));
+            }
+        }
+        intervals.extend(Self::commodity_day());
+        let name = match night_end {
+            Some(end) => format!("commodity_{}", end.format("%H%M")),
+            None => "commodity".to_owned(),
+        };
+        Self { name, intervals }
+    }
+
+    /// 中金所股指期货: 9:30~11:30, 13:00~15:00
+    pub fn cffex_index() -> Self {
+        Self {
+            name: "cffex_index".to_owned(),
+            intervals: Self::afternoon_13(make_time(9, 30, 0), make_time(15, 0, 0)),
+        }
+    }
+
+    /// 中金所国债期货: 9:30~11:30, 13:00~15:15
+    pub fn cffex_bond() -> Self {
+        Self {
+            name: "cffex_bond".to_owned(),
+            intervals: Self::afternoon_13(make_time(9, 30, 0), make_time(15, 15, 0)),
+        }
+    }
+
+    /// 股票连续竞价: 9:30~11:30, 13:00~15:00
+    pub fn stock() -> Self {
+        Self {
+            name: "stock".to_owned(),
+            intervals: Self::afternoon_13(make_time(9, 30, 0), make_time(15, 0, 0)),
+        }
+    }
+
+    /// 兼容以前的is_finance_item参数: 日盘9:00开始, 金融期货15:15收盘, 其他15:00收盘, 夜盘21:00开始
+    pub fn from_finance_flag(is_finance_item: bool) -> Self {
+        let day_end = if is_finance_item {
+            make_time(15, 15, 0)
+        } else {
+            make_time(15, 0, 0)
+        };
+        let midnight = make_time(0, 0, 0);
+        Self {
+            name: if is_finance_item {
+                "finance"
+            } else {
+                "commodity"
+            }
+            .to_owned(),
+            intervals: vec![
+                SessionInterval::new(SessionPart::Night, make_time(21, 0, 0), midnight),
+                SessionInterval::new(SessionPart::Day, make_time(9, 0, 0), day_end),
+            ],
+        }
+    }
+
+    fn commodity_day() -> [SessionInterval; 3] {
+        [
+            SessionInterval::new(SessionPart::Day, make_time(9, 0, 0), make_time(10, 15, 0)),
+            SessionInterval::new(SessionPart::Day, make_time(10, 30, 0), make_time(11, 30, 0)),
+            SessionInterval::new(SessionPart::Day, make_time(13, 30, 0), make_time(15, 0, 0)),
+        ]
+    }
+
+    fn afternoon_13(day_begin: MyTimeType, day_end: MyTimeType) -> Vec<SessionInterval> {
+        vec![
+            SessionInterval::new(SessionPart::Day, day_begin, make_time(11, 30, 0)),
+            SessionInterval::new(SessionPart::Day, make_time(13, 0, 0), day_end),
+        ]
+    }
+
+    /// 是否有夜盘
+    pub fn has_night(&self) -> bool {
+        self.intervals.iter().any(|i| i.part == SessionPart::Night)
+    }
+
+    /// 夜盘开始时间
+    pub fn night_begin(&self) -> Option<MyTimeType> {
+        self.intervals
+            .iter()
+            .find(|i| i.part == SessionPart::Night)
+            .map(|i| i.begin)
+    }
+
+    /// 日盘开盘时间
+    pub fn day_begin(&self) -> Option<MyTimeType> {
+        self.intervals
+            .iter()
+            .find(|i| i.part == SessionPart::Day)
+            .map(|i| i.begin)
+    }
+
+    /// 日盘收盘时间
+    pub fn day_end(&self) -> Option<MyTimeType> {
+        self.intervals
+            .iter()
+            .rev()
+            .find(|i| i.part == SessionPart::Day)
+            .map(|i| i.end)
+    }
+
+    /// 找到包含time的区间
+    pub fn interval_at(&self, time: &MyTimeType) -> Option<&SessionInterval> {
+        self.intervals.iter().find(|i| i.contains(time))
+    }
+}
+
+/// 品种代码: 取合约代码开头的字母部分, 统一为大写, 如 rb2501 => RB, IF2412 => IF
+pub fn product_of(instrument: &str) -> String {
+    instrument
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// 夜盘结束时间(时,分), None表示没有夜盘
+type NightEnd = Option<(u32, u32)>;
+
+/// 内置品种表: (品种, 交易所, 夜盘结束时间)
+const COMMODITY_PRODUCTS: &[(&str, Exchange, NightEnd)] = &[
+    // 上期所
+    ("CU", Exchange::SHFE, Some((1, 0))),
+    ("AL", Exchange::SHFE, Some((1, 0))),
+    ("ZN", Exchange::SHFE, Some((1, 0))),
+    ("PB", Exchange::SHFE, Some((1, 0))),
+    ("NI", Exchange::SHFE, Some((1, 0))),
+    ("SN", Exchange::SHFE, Some((1, 0))),
+    ("SS", Exchange::SHFE, Some((1, 0))),
+    ("AO", Exchange::SHFE, Some((1, 0))),
+    ("AU", Exchange::SHFE, Some((2, 30))),
+    ("AG", Exchange::SHFE, Some((2, 30))),
+    ("RB", Exchange::SHFE, Some((23, 0))),
+    ("HC", Exchange::SHFE, Some((23, 0))),
+    ("BU", Exchange::SHFE, Some((23, 0))),
+    ("RU", Exchange::SHFE, Some((23, 0))),
+    ("FU", Exchange::SHFE, Some((23, 0))),
+    ("SP", Exchange::SHFE, Some((23, 0))),
+    ("BR", Exchange::SHFE, Some((23, 0))),
+    ("WR", Exchange::SHFE, None),
+    // 能源中心
+    ("SC", Exchange::INE, Some((2, 30))),
+    ("BC", Exchange::INE, Some((1, 0))),
+    ("NR", Exchange::INE, Some((23, 0))),
+    ("LU", Exchange::INE, Some((23, 0))),
+    ("EC", Exchange::INE, None),
+    // 大商所
+    ("A", Exchange::DCE, Some((23, 0))),
+    ("B", Exchange::DCE, Some((23, 0))),
+    ("M", Exchange::DCE, Some((23, 0))),
+    ("Y", Exchange::DCE, Some((23, 0))),
+    ("P", Exchange::DCE, Some((23, 0))),
+    ("C", Exchange::DCE, Some((23, 0))),
+    ("CS", Exchange::DCE, Some((23, 0))),
+    ("L", Exchange::DCE, Some((23, 0))),
+    ("V", Exchange::DCE, Some((23, 0))),
+    ("PP", Exchange::DCE, Some((23, 0))),
+    ("J", Exchange::DCE, Some((23, 0))),
+    ("JM", Exchange::DCE, Some((23, 0))),
+    ("I", Exchange::DCE, Some((23, 0))),
+    ("EG", Exchange::DCE, Some((23, 0))),
+    ("EB", Exchange::DCE, Some((23, 0))),
+    ("PG", Exchange::DCE, Some((23, 0))),
+    ("RR", Exchange::DCE, Some((23, 0))),
+    ("JD", Exchange::DCE, None),
+    ("LH", Exchange::DCE, None),
+    ("FB", Exchange::DCE, None),
+    ("BB", Exchange::DCE, None),
+    // 郑商所
+    ("SR", Exchange::CZCE, Some((23, 0))),
+    ("CF", Exchange::CZCE, Some((23, 0))),
+    ("TA", Exchange::CZCE, Some((23, 0))),
+    ("MA", Exchange::CZCE, Some((23, 0))),
+    ("FG", Exchange::CZCE, Some((23, 0))),
+    ("RM", Exchange::CZCE, Some((23, 0))),
+    ("OI", Exchange::CZCE, Some((23, 0))),
+    ("ZC", Exchange::CZCE, Some((23, 0))),
+    ("SA", Exchange::CZCE, Some((23, 0))),
+    ("PF", Exchange::CZCE, Some((23, 0))),
+    ("CY", Exchange::CZCE, Some((23, 0))),
+    ("PX", Exchange::CZCE, Some((23, 0))),
+    ("SH", Exchange::CZCE, Some((23, 0))),
+    ("AP", Exchange::CZCE, None),
+    ("CJ", Exchange::CZCE, None),
+    ("PK", Exchange::CZCE, None),
+    ("UR", Exchange::CZCE, None),
+    ("SF", Exchange::CZCE, None),
+    ("SM", Exchange::CZCE, None),
+    ("WH", Exchange::CZCE, None),
+    ("PM", Exchange::CZCE, None),
+    ("RI", Exchange::CZCE, None),
+    ("JR", Exchange::CZCE, None),
+    ("LR", Exchange::CZCE, None),
+    ("RS", Exchange::CZCE, None),
+    // 广期所
+    ("SI", Exchange::GFEX, None),
+    ("LC", Exchange::GFEX, None),
+    ("PS", Exchange::GFEX, None),
+];
+
+/// 一次交易时段变更: (生效日期(年,月,日), 夜盘结束时间)
+type SessionChange = ((i32, u32, u32), NightEnd);
+
+/// 交易时段的历史变更: (品种, [变更]), 第一次变更之前没有夜盘;
+///
+/// 收录有夜盘的品种夜盘开设及夜盘收盘时间调整的日期, 上市时即有夜盘的品种以上市日期为准
+const COMMODITY_SESSION_CHANGES: &[(&[&str], &[SessionChange])] = &[
+    (&["AU", "AG"], &[((2013, 7, 5), Some((2, 30)))]),
+    (&["CU", "AL", "ZN", "PB"], &[((2013, 12, 20), Some((1, 0)))]),
+    (&["NI", "SN"], &[((2015, 3, 27), Some((1, 0)))]),
+    (&["SS"], &[((2019, 9, 25), Some((1, 0)))]),
+    (&["AO"], &[((2023, 6, 19), Some((1, 0)))]),
+    (&["FU"], &[((2018, 7, 16), Some((23, 0)))]),
+    (&["SP"], &[((2018, 11, 27), Some((23, 0)))]),
+    (&["BR"], &[((2023, 7, 28), Some((23, 0)))]),
+    (&["SC"], &[((2018, 3, 26), Some((2, 30)))]),
+    (&["BC"], &[((2020, 11, 19), Some((1, 0)))]),
+    (&["NR"], &[((2019, 8, 12), Some((23, 0)))]),
+    (&["LU"], &[((2020, 6, 22), Some((23, 0)))]),
+    (
+        &["RB", "HC", "BU"],
+        &[
+            ((2014, 12, 26), Some((1, 0))),
+            ((2016, 5, 3), Some((23, 0))),
+        ],
+    ),
+    (&["RU"], &[((2014, 12, 26), Some((23, 0)))]),
+    (
+        &["A", "B", "M", "Y", "P", "J", "JM"],
+        &[
+            ((2014, 7, 4), Some((2, 30))),
+            ((2015, 5, 8), Some((23, 30))),
+            ((2019, 3, 29), Some((23, 0))),
+        ],
+    ),
+    (
+        &["I"],
+        &[
+            ((2014, 12, 26), Some((2, 30))),
+            ((2015, 5, 8), Some((23, 30))),
+            ((2019, 3, 29), Some((23, 0))),
+        ],
+    ),
+    (
+        &["L", "V", "PP"],
+        &[
+            ((2015, 5, 8), Some((23, 30))),
+            ((2019, 3, 29), Some((23, 0))),
+        ],
+    ),
+    (&["C", "CS"], &[((2019, 3, 29), Some((23, 0)))]),
+    (
+        &["EG"],
+        &[
+            ((2018, 12, 10), Some((23, 30))),
+            ((2019, 3, 29), Some((23, 0))),
+        ],
+    ),
+    (&["EB"], &[((2019, 9, 26), Some((23, 0)))]),
+    (&["RR"], &[((2019, 8, 16), Some((23, 0)))]),
+    (&["PG"], &[((2020, 3, 30), Some((23, 0)))]),
+    (
+        &["SR", "CF", "TA", "MA", "FG", "RM", "OI", "ZC"],
+        &[
+            ((2014, 12, 12), Some((23, 30))),
+            ((2019, 12, 12), Some((23, 0))),
+        ],
+    ),
+    (
+        &["CY"],
+        &[
+            ((2017, 8, 18), Some((23, 30))),
+            ((2019, 12, 12), Some((23, 0))),
+        ],
+    ),
+    (&["SA"], &[((2019, 12, 6), Some((23, 0)))]),
+    (&["PF"], &[((2020, 10, 12), Some((23, 0)))]),
+    (&["PX", "SH"], &[((2023, 9, 15), Some((23, 0)))]),
+];
+
+/// 一段时间内有效的交易时段, 有效期为 [from, to), None表示不限
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EffectiveSession {
+    pub from: Option<MyDateType>,
+    pub to: Option<MyDateType>,
+    pub template: SessionTemplate,
+}
+
+impl EffectiveSession {
+    pub fn new(
+        from: Option<MyDateType>,
+        to: Option<MyDateType>,
+        template: SessionTemplate,
+    ) -> Self {
+        Self { from, to, template }
+    }
+
+    /// date是否在有效期内
+    pub fn is_effective(&self, date: &MyDateType) -> bool {
+        self.from.is_none_or(|from| date >= &from) && self.to.is_none_or(|to| date < &to)
+    }
+}
+
+/// 一个品种交易时段的变更历史, 按有效期排序且互不重叠
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SessionHistory {
+    sessions: Vec<EffectiveSession>,
+}
+
+impl SessionHistory {
+    /// sessions必须按有效期排序且互不重叠
+    pub fn new(sessions: Vec<EffectiveSession>) -> Result<Self> {
+        if sessions.is_empty() {
+            return Err(anyhow!("SessionHistory: sessions is empty."));
+        }
+        for session in sessions.iter() {
+            if let (Some(from), Some(to)) = (session.from, session.to)
+                && from >= to
+            {
+                return Err(anyhow!(
+                    "SessionHistory: invalid period {} ~ {} of `{}`",
+                    from,
+                    to,
+                    session.template.name
+                ));
+            }
+        }
+        for pair in sessions.windows(2) {
+            let ordered = match (pair[0].to, pair[1].from) {
+                (Some(to), Some(from)) => to <= from,
+                _ => false,
+            };
+            if !ordered {
+                return Err(anyhow!(
+                    "SessionHistory: `{}` overlaps with `{}`",
+                    pair[0].template.name,
+                    pair[1].template.name
+                ));
+            }
+        }
+        Ok(Self { sessions })
+    }
+
+    /// 一直有效的交易时段
+    pub fn constant(template: SessionTemplate) -> Self {
+        Self {
+            sessions: vec![EffectiveSession::new(None, None, template)],
+        }
+    }
+
+    /// 由按日期排序的变更列表构造: (生效日期, 交易时段), 每一条有效至下一条生效为止,
+    /// initial为第一次变更之前的交易时段
+    pub fn from_changes(
+        initial: SessionTemplate,
+        changes: Vec<(MyDateType, SessionTemplate)>,
+    ) -> Result<Self> {
+        let mut sessions = Vec::with_capacity(changes.len() + 1);
+        let mut from: Option<MyDateType> = None;
+        let mut template = initial;
+        for (date, next) in changes.into_iter() {
+            sessions.push(EffectiveSession::new(from, Some(date), template));
+            from = Some(date);
+            template = next;
+        }
+        sessions.push(EffectiveSession::new(from, None, template));
+        Self::new(sessions)
+    }
+
+    pub fn sessions(&self) -> &[EffectiveSession] {
+        &self.sessions
+    }
+
+    /// date当天有效的交易时段
+    pub fn template_at(&self, date: &MyDateType) -> Option<&SessionTemplate> {
+        self.sessions
+            .iter()
+            .find(|s| s.is_effective(date))
+            .map(|s| &s.template)
+    }
+
+    /// 最新的交易时段
+    pub fn current(&self) -> &SessionTemplate {
+        &self.sessions.last().expect("no fail").template
+    }
+}
+
+/// 品种的交易所及交易时段历史
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductSession {
+    pub exchange: Exchange,
+    pub history: SessionHistory,
+}
+
+/// 品种 => 交易时段 的查询表, 品种代码不区分大小写, 也可以直接用合约代码查询
+#[derive(Debug, Clone, Default)]
+pub struct SessionRegistry {
+    products: HashMap<String, ProductSession>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 内置的期货品种交易时段, 包含主要品种的历史变更
+    pub fn buildin() -> Self {
+        let mut registry = Self::new();
+        let night_end_time = |night_end: &NightEnd| night_end.map(|(h, m)| make_time(h, m, 0));
+        for (product, exchange, night_end) in COMMODITY_PRODUCTS.iter() {
+            let template = SessionTemplate::commodity(night_end_time(night_end));
+            registry.insert(product, *exchange, template);
+        }
+        for (products, changes) in COMMODITY_SESSION_CHANGES.iter() {
+            let changes: Vec<(MyDateType, SessionTemplate)> = changes
+                .iter()
+                .map(|((y, m, d), night_end)| {
+                    let template = SessionTemplate::commodity(night_end_time(night_end));
+                    (make_date(*y, *m, *d), template)
+                })
+                .collect();
+            let history = SessionHistory::from_changes(SessionTemplate::commodity(None), changes)
+                .expect("buildin session history");
+            for product in products.iter() {
+                let exchange = registry.exchange(product).expect("buildin product");
+                registry.insert_history(product, exchange, history.clone());
+            }
+        }
+
+        // 中金所: 2016-01-01起股指期货15:00收盘; 2020-07-20起国债期货9:30开盘
+        let cffex_old = |name: &str| {
+            let mut template = SessionTemplate::cffex_bond();
+            template.name = name.to_owned();
+            template.intervals[0].begin = make_time(9, 15, 0);
+            template
+        };
+        let history = SessionHistory::from_changes(
+            cffex_old("cffex_index_1515"),
+            vec![(make_date(2016, 1, 1), SessionTemplate::cffex_index())],
+        )
+        .expect("buildin session history");
+        for product in ["IF", "IH", "IC"] {
+            registry.insert_history(product, Exchange::CFFEX, history.clone());
+        }
+        registry.insert("IM", Exchange::CFFEX, SessionTemplate::cffex_index());
+        let history = SessionHistory::from_changes(
+            cffex_old("cffex_bond_0915"),
+            vec![(make_date(2020, 7, 20), SessionTemplate::cffex_bond())],
+        )
+        .expect("buildin session history");
+        for product in ["T", "TF", "TS"] {
+            registry.insert_history(product, Exchange::CFFEX, history.clone());
+        }
+        registry.insert("TL", Exchange::CFFEX, SessionTemplate::cffex_bond());
+        registry
+    }
+
+    /// 添加或替换某个品种的交易时段, 一直有效
+    pub fn insert(&mut self, product: &str, exchange: Exchange, template: SessionTemplate) {
+        self.insert_history(product, exchange, SessionHistory::constant(template));
+    }
+
+    /// 添加或替换某个品种的交易时段历史
+    pub fn insert_history(&mut self, product: &str, exchange: Exchange, history: SessionHistory) {
+        self.products
+            .insert(product_of(product), ProductSession { exchange, history });
+    }
+
+    /// 按品种或合约代码查询
+    pub fn get(&self, product: &str) -> Option<&ProductSession> {
+        self.products.get(&product_of(product))
+    }
+
+    /// 按品种或合约代码查询当前的交易时段
+    pub fn template(&self, product: &str) -> Result<&SessionTemplate> {
+        self.get(product)
+            .map(|p| p.history.current())
+            .ok_or_else(|| anyhow!("session of product `{}` not found", product))
+    }
+
+    /// 按品种或合约代码查询date当天有效的交易时段
+    pub fn template_at(&self, product: &str, date: &MyDateType) -> Result<&SessionTemplate> {
+        let history = &self
+            .get(product)
+            .ok_or_else(|| anyhow!("session of product `{}` not found", product))?
+            .history;
+        history
+            .template_at(date)
+            .ok_or_else(|| anyhow!("session of product `{}` not effective at {}", product, date))
+    }
+
+    /// 按品种或合约代码查询交易所
+    pub fn exchange(&self, product: &str) -> Option<Exchange> {
+        self.get(product).map(|p| p.exchange)
+    }
+
+    /// 根据输入时间获取品种的交易日, 使用当时有效的交易时段,
+    /// 参见TradingdayCache::trading_day_from_datetime()
+    pub fn trading_day_from_datetime<C: TradingdayCache + ?Sized>(
+        &self,
+        calendar: &C,
+        product: &str,
+        input: &MyDateTimeType,
+        method: NotTradingSearchMethod,
+    ) -> Result<MyDateType> {
+        let template = self.template_at(product, &input.date())?;
+        let tday = calendar.trading_day_from_datetime(input, method, template)?;
+        // 夜盘属于下一个交易日, 交易时段应该以交易日为准
+        match self.template_at(product, &tday) {
+            Ok(other) if other != template => {
+                calendar.trading_day_from_datetime(input, method, other)
+            }
+            _ => Ok(tday),
+        }
+    }
+
+    /// 根据tick的时间获取品种的(交易日, 所属1分钟K线的开始时间), 使用当时有效的交易时段,
+    /// 参见TradingdayCache::bar_of_tick_with()
+    pub fn bar_of_tick<C: TradingdayCache + ?Sized>(
+        &self,
+        calendar: &C,
+        product: &str,
+        input: &MyDateTimeType,
+    ) -> Result<(MyDateType, MyDateTimeType)> {
+        let template = self.template_at(product, &input.date())?;
+        let result = calendar.bar_of_tick_with(input, template);
+        // 夜盘属于下一个交易日, 交易时段应该以交易日为准
+        match &result {
+            Ok((tday, _)) => match self.template_at(product, tday) {
+                Ok(other) if other != template => calendar.bar_of_tick_with(input, other),
+                _ => result,
+            },
+            Err(_) => result,
+        }
+    }
+}
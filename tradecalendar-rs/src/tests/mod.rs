@@ -1 +1,12 @@
 mod test_3;
+mod test_backtest;
+mod test_bars;
+mod test_clock;
+mod test_combine;
+mod test_exchange;
+mod test_hkex;
+mod test_holiday;
+mod test_intervals;
+mod test_overrides;
+mod test_session;
+mod test_tick;
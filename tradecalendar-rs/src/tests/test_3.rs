@@ -242,4 +242,236 @@ mod tests {
         assert_eq!(mgr.current_tday(), &y20210121);
         Ok(())
     }
+
+    #[test]
+    fn ordinal_and_offset() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let t = make_date(2024, 9, 30);
+        let ordinal = calendar.ordinal(&t).unwrap();
+        assert_eq!(calendar.tradingday_at_ordinal(ordinal).unwrap().date, t);
+        assert_eq!(calendar.ordinal(&make_date(2024, 10, 1)), None);
+        assert_eq!(calendar.ordinal(&make_date(2024, 10, 8)), Some(ordinal + 1));
+
+        assert_eq!(calendar.offset(&t, 0)?.date, t);
+        assert_eq!(calendar.offset(&t, 1)?.date, make_date(2024, 10, 8));
+        assert_eq!(calendar.offset(&t, 3)?.date, make_date(2024, 10, 10));
+        assert_eq!(calendar.offset(&t, -5)?.date, make_date(2024, 9, 23));
+        // 非交易日, 与get_next_trading_day()/get_prev_trading_day()一致
+        let holiday = make_date(2024, 10, 3);
+        assert_eq!(calendar.offset(&holiday, 1)?.date, make_date(2024, 10, 8));
+        assert_eq!(calendar.offset(&holiday, -1)?.date, t);
+        assert_eq!(
+            calendar.offset(&holiday, -2)?.date,
+            calendar.get_prev_trading_day(&holiday, 2)?.date
+        );
+        assert!(calendar.offset(&holiday, 0).is_err());
+        assert!(calendar.offset(&t, -100_000).is_err());
+        assert!(calendar.offset(&t, i64::MAX / 2).is_err());
+        assert!(calendar.offset(&t, i64::MAX).is_err());
+        assert!(calendar.offset(&holiday, i64::MIN).is_err());
+
+        assert_eq!(calendar.signed_distance(&t, &t)?, 0);
+        assert_eq!(calendar.signed_distance(&t, &make_date(2024, 10, 10))?, 3);
+        assert_eq!(calendar.signed_distance(&make_date(2024, 10, 10), &t)?, -3);
+        assert_eq!(
+            calendar.signed_distance(&holiday, &make_date(2024, 10, 8))?,
+            1
+        );
+        assert_eq!(
+            calendar.signed_distance(&make_date(2024, 10, 8), &holiday)?,
+            -1
+        );
+        assert_eq!(
+            calendar.signed_distance(&holiday, &make_date(2024, 10, 5))?,
+            0
+        );
+        let d = calendar.signed_distance(&make_date(2024, 9, 23), &make_date(2024, 10, 10))?;
+        assert_eq!(
+            calendar.offset(&make_date(2024, 9, 23), d)?.date,
+            make_date(2024, 10, 10)
+        );
+        assert!(
+            calendar
+                .signed_distance(&make_date(2023, 1, 1), &t)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn period_anchors() -> Result<()> {
+        let d = |y, m, d| make_date(y, m, d);
+        assert_eq!(
+            CalendarPeriod::Month.bounds(&d(2024, 2, 10)),
+            (d(2024, 2, 1), d(2024, 2, 29))
+        );
+        assert_eq!(
+            CalendarPeriod::Quarter.bounds(&d(2024, 11, 5)),
+            (d(2024, 10, 1), d(2024, 12, 31))
+        );
+        assert_eq!(
+            CalendarPeriod::Week.bounds(&d(2024, 12, 31)),
+            (d(2024, 12, 30), d(2025, 1, 5))
+        );
+
+        let calendar = get_buildin_calendar(Some(d(2024, 1, 1)))?;
+        let date = d(2024, 9, 18);
+        assert_eq!(calendar.first_in_month(&date)?.date, d(2024, 9, 2));
+        assert_eq!(calendar.last_in_month(&date)?.date, d(2024, 9, 30));
+        assert_eq!(calendar.nth_in_month(&date, -2)?.date, d(2024, 9, 27));
+        assert!(calendar.nth_in_month(&date, 0).is_err());
+        assert!(calendar.nth_in_month(&date, 20).is_err());
+        assert!(calendar.nth_in_month(&date, -20).is_err());
+        // 国庆后第三个交易日
+        assert_eq!(
+            calendar.nth_in_quarter(&d(2024, 12, 1), 3)?.date,
+            d(2024, 10, 10)
+        );
+        assert_eq!(calendar.first_in_year(&date)?.date, d(2024, 1, 2));
+        assert_eq!(calendar.last_in_year(&date)?.date, d(2024, 12, 31));
+        // 国庆所在的周只有一个交易日
+        assert_eq!(
+            calendar.first_in_week(&d(2024, 10, 3))?.date,
+            d(2024, 9, 30)
+        );
+        assert_eq!(calendar.last_in_week(&d(2024, 10, 3))?.date, d(2024, 9, 30));
+        assert!(calendar.nth_in_week(&d(2024, 10, 3), 2).is_err());
+
+        assert_eq!(
+            calendar.index_in_period(&d(2024, 9, 30), CalendarPeriod::Month)?,
+            19
+        );
+        assert_eq!(
+            calendar.index_in_period(&d(2024, 9, 29), CalendarPeriod::Month)?,
+            18
+        );
+        assert_eq!(
+            calendar.index_in_period(&d(2024, 10, 10), CalendarPeriod::Quarter)?,
+            3
+        );
+        assert_eq!(
+            calendar.index_in_period(&d(2024, 10, 5), CalendarPeriod::Quarter)?,
+            0
+        );
+        // 周期超出日历范围
+        assert!(calendar.first_in_year(&d(2023, 12, 29)).is_err());
+        assert!(calendar.first_in_week(&d(2024, 1, 1)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn next_transition() -> Result<()> {
+        let mut mgr = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let sunday = make_date(2024, 9, 29);
+        let monday = make_date(2024, 9, 30);
+        use TransitionKind::*;
+
+        let mut at = date_at_hms(&friday, 10, 0, 0);
+        let expected = vec![
+            (date_at_hms(&friday, 15, 30, 1), vec![TradingStopped]),
+            (date_at_hms(&friday, 19, 30, 0), vec![TradingDayChanged]),
+            (date_at_hms(&friday, 20, 30, 0), vec![TradingStarted]),
+            (date_at_hms(&saturday, 0, 0, 0), vec![NaturalDayChanged]),
+            (date_at_hms(&saturday, 2, 31, 1), vec![TradingStopped]),
+            (date_at_hms(&sunday, 0, 0, 0), vec![NaturalDayChanged]),
+        ];
+        for (instant, kinds) in expected {
+            let transition = mgr.next_transition(&at, true)?;
+            assert_eq!(transition, Transition { at: instant, kinds });
+            at = transition.at;
+        }
+
+        // 国庆节前没有夜盘, 0点切换交易日
+        let transition = mgr.next_transition(&date_at_hms(&monday, 16, 0, 0), true)?;
+        assert_eq!(
+            transition,
+            Transition {
+                at: date_at_hms(&make_date(2024, 10, 1), 0, 0, 0),
+                kinds: vec![NaturalDayChanged, TradingDayChanged],
+            }
+        );
+
+        // 按交易时段判断is_trading
+        mgr.set_session_template(Some(crate::SessionTemplate::commodity(Some(make_time(
+            23, 0, 0,
+        )))));
+        let transition = mgr.next_transition(&date_at_hms(&friday, 10, 0, 0), true)?;
+        assert_eq!(transition.at, date_at_hms(&friday, 10, 15, 0));
+        assert_eq!(transition.kinds, vec![TradingStopped]);
+
+        // 超出日历范围时, 只有fail_safe才会继续
+        let out_of_range = date_at_hms(&make_date(2030, 1, 2), 10, 0, 0);
+        assert!(mgr.next_transition(&out_of_range, false).is_err());
+        assert!(mgr.next_transition(&out_of_range, true).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn calendar_events() -> Result<()> {
+        use std::sync::{Arc, Mutex};
+
+        let mut mgr = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        mgr.add_listener(move |event| sink.lock().unwrap().push(event.clone()));
+
+        let friday = make_date(2024, 9, 27);
+        let events = mgr.time_changed_events(&date_at_hms(&friday, 10, 0, 0), false)?;
+        assert_eq!(
+            events,
+            vec![
+                CalendarEvent::NaturalDayChanged {
+                    prev: make_date(1970, 1, 1),
+                    curr: friday
+                },
+                CalendarEvent::TradingDayChanged {
+                    prev: make_date(2024, 9, 26),
+                    curr: friday,
+                    next: make_date(2024, 9, 30)
+                },
+                CalendarEvent::TradingStarted,
+            ]
+        );
+        assert!(
+            mgr.time_changed_events(&date_at_hms(&friday, 10, 1, 0), false)?
+                .is_empty()
+        );
+        let events = mgr.time_changed_events(&date_at_hms(&friday, 19, 30, 0), false)?;
+        assert_eq!(
+            events,
+            vec![
+                CalendarEvent::TradingDayChanged {
+                    prev: friday,
+                    curr: make_date(2024, 9, 30),
+                    next: make_date(2024, 10, 8)
+                },
+                CalendarEvent::TradingStopped,
+            ]
+        );
+
+        // 日历数据到2026-12-31为止
+        let day = make_date(2026, 12, 10);
+        let events = mgr.time_changed_events(&date_at_hms(&day, 10, 0, 0), false)?;
+        assert_eq!(
+            events.last(),
+            Some(&CalendarEvent::CoverageEnding { days_left: 21 })
+        );
+        // 超出日历范围, 按工作日推算
+        let day = make_date(2027, 1, 5);
+        let events = mgr.time_changed_events(&date_at_hms(&day, 10, 0, 0), true)?;
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, CalendarEvent::FailSafeUsed { .. }))
+        );
+
+        // time_changed()同样通知listener, 仍然超出日历范围
+        mgr.time_changed(&date_at_hms(&day, 16, 0, 0), true)?;
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3 + 2 + 4 + events.len() + 2);
+        assert_eq!(received[received.len() - 2], CalendarEvent::TradingStopped);
+        Ok(())
+    }
 }
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::sync::{Arc, Mutex};
+
+    use crate::jcswitch::*;
+    use crate::{
+        BacktestCalendar, BacktestStep, CalendarEvent, TradingCheckConfig, get_buildin_calendar,
+    };
+
+    #[test]
+    fn backtest_calendar() -> Result<()> {
+        let friday = make_date(2024, 9, 27);
+        let monday = make_date(2024, 9, 30);
+        let start = date_at_hms(&friday, 9, 0, 0);
+        let end = date_at_hms(&monday, 9, 0, 0);
+
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        let replay = BacktestCalendar::new(calendar, start, end, BacktestStep::Transitions)?;
+        let steps = replay.collect::<Result<Vec<_>>>()?;
+        let times: Vec<_> = steps.iter().map(|(at, _)| *at).collect();
+        assert_eq!(
+            times,
+            vec![
+                start,
+                date_at_hms(&friday, 15, 30, 1),
+                date_at_hms(&friday, 19, 30, 0),
+                date_at_hms(&friday, 20, 30, 0),
+                date_at_hms(&make_date(2024, 9, 28), 0, 0, 0),
+                date_at_hms(&make_date(2024, 9, 28), 2, 31, 1),
+                date_at_hms(&make_date(2024, 9, 29), 0, 0, 0),
+                date_at_hms(&monday, 0, 0, 0),
+                date_at_hms(&monday, 8, 30, 0),
+            ]
+        );
+        // 第一步包含初始化的事件
+        assert!(steps[0].1.contains(&CalendarEvent::TradingStarted));
+        assert_eq!(steps[2].1.len(), 1);
+        assert!(matches!(
+            steps[2].1[0],
+            CalendarEvent::TradingDayChanged { curr, .. } if curr == monday
+        ));
+
+        // 按分钟步进, 使用自定义的配置
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        let end = date_at_hms(&friday, 16, 0, 0);
+        let mut replay = BacktestCalendar::new(calendar, start, end, BacktestStep::Every(60))?;
+        let cfg = TradingCheckConfig {
+            day_end: make_time(15, 0, 0),
+            ..Default::default()
+        };
+        replay.set_config(&cfg)?;
+        let mut count = 0;
+        let mut stopped_at = None;
+        for step in replay.by_ref() {
+            let (at, events) = step?;
+            count += 1;
+            if events.contains(&CalendarEvent::TradingStopped) {
+                stopped_at = Some(at);
+            }
+        }
+        assert_eq!(count, 7 * 60 + 1);
+        assert_eq!(stopped_at, Some(date_at_hms(&friday, 15, 1, 0)));
+        assert_eq!(replay.calendar().current_time(), &end);
+
+        // new()中的reset不通知listener, 第一步的事件与返回值相同
+        let mut calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        calendar.add_listener(move |event| sink.lock().unwrap().push(event.clone()));
+        let mut replay = BacktestCalendar::new(calendar, start, end, BacktestStep::Transitions)?;
+        assert!(received.lock().unwrap().is_empty());
+        let (_, events) = replay.next().expect("first step")?;
+        assert_eq!(*received.lock().unwrap(), events);
+
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        assert!(BacktestCalendar::new(calendar, end, start, BacktestStep::Every(60)).is_err());
+        Ok(())
+    }
+}
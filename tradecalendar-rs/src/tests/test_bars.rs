@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::{SessionTemplate, TradingdayCache, get_buildin_calendar};
+
+    #[test]
+    fn bar_timestamps() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let monday = make_date(2024, 9, 30);
+
+        // 螺纹夜盘21:00~23:00, 日盘225分钟
+        let bars = calendar.bar_timestamps(&monday, "rb2501", 1)?;
+        assert_eq!(bars.len(), 345);
+        assert_eq!(
+            bars[0],
+            (
+                date_at_hms(&friday, 21, 0, 0),
+                date_at_hms(&friday, 21, 1, 0)
+            )
+        );
+        assert_eq!(
+            bars[344],
+            (
+                date_at_hms(&monday, 14, 59, 0),
+                date_at_hms(&monday, 15, 0, 0)
+            )
+        );
+        // 10:15~10:30小节休息没有K线
+        assert!(bars.contains(&(
+            date_at_hms(&monday, 10, 14, 0),
+            date_at_hms(&monday, 10, 15, 0)
+        )));
+        assert!(bars.contains(&(
+            date_at_hms(&monday, 10, 30, 0),
+            date_at_hms(&monday, 10, 31, 0)
+        )));
+        assert!(
+            !bars
+                .iter()
+                .any(|(open, _)| *open == date_at_hms(&monday, 10, 20, 0))
+        );
+
+        // 黄金夜盘到凌晨2:30
+        let bars = calendar.bar_timestamps(&monday, "au2412", 1)?;
+        assert_eq!(bars.len(), 555);
+        assert_eq!(bars[179].1, date_at_hms(&saturday, 0, 0, 0));
+
+        // 60分钟线跨越小节休息和午休, 日盘收盘不足60分钟的单独一根
+        let bars = calendar.bar_timestamps(&monday, "rb2501", 60)?;
+        assert_eq!(bars.len(), 6);
+        assert_eq!(
+            &bars[2..],
+            &[
+                (
+                    date_at_hms(&monday, 9, 0, 0),
+                    date_at_hms(&monday, 10, 0, 0)
+                ),
+                (
+                    date_at_hms(&monday, 10, 0, 0),
+                    date_at_hms(&monday, 11, 15, 0)
+                ),
+                (
+                    date_at_hms(&monday, 11, 15, 0),
+                    date_at_hms(&monday, 14, 15, 0)
+                ),
+                (
+                    date_at_hms(&monday, 14, 15, 0),
+                    date_at_hms(&monday, 15, 0, 0)
+                ),
+            ]
+        );
+
+        // 黄金30分钟线: 夜盘330分钟11根, 不跨到日盘
+        let bars = calendar.bar_timestamps(&monday, "au2412", 30)?;
+        assert_eq!(bars[10].1, date_at_hms(&saturday, 2, 30, 0));
+        assert_eq!(bars[11].0, date_at_hms(&monday, 9, 0, 0));
+        assert_eq!(bars.len(), 11 + 8);
+
+        let bars = calendar.bar_timestamps_with(&monday, &SessionTemplate::stock(), 30)?;
+        assert_eq!(bars.len(), 8);
+        assert_eq!(bars[0].0, date_at_hms(&monday, 9, 30, 0));
+        assert_eq!(bars[7].1, date_at_hms(&monday, 15, 0, 0));
+
+        assert!(calendar.bar_timestamps(&monday, "rb2501", 0).is_err());
+        assert!(calendar.bar_timestamps(&saturday, "rb2501", 1).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::jcswitch::*;
+    use crate::{
+        CalendarClock, CalendarEvent, Clock, FixedClock, ManualClock, ReplayClock,
+        get_buildin_calendar,
+    };
+
+    #[test]
+    fn calendar_clock() -> Result<()> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        rt.block_on(async {
+            // 使用固定时钟, 不受运行测试时的真实时间影响
+            let monday_open = date_at_hms(&make_date(2024, 9, 30), 10, 0, 0);
+            let mut calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+            calendar.set_clock(Arc::new(FixedClock(monday_open)));
+            let mut clock = CalendarClock::new(calendar, 16);
+            let mut events = clock.subscribe();
+            let state = clock.watch();
+
+            let friday = make_date(2024, 9, 27);
+            clock.step(&date_at_hms(&friday, 10, 0, 0))?;
+            assert!(matches!(
+                events.recv().await?,
+                CalendarEvent::NaturalDayChanged { .. }
+            ));
+            assert!(matches!(
+                events.recv().await?,
+                CalendarEvent::TradingDayChanged { .. }
+            ));
+            assert_eq!(events.recv().await?, CalendarEvent::TradingStarted);
+            assert_eq!(state.borrow().curr_tday, friday);
+            assert!(state.borrow().is_trading);
+
+            // 启动之后立即按时钟的当前时间刷新状态, 然后可以停止
+            let handle = clock.spawn();
+            let mut state = handle.watch();
+            state.changed().await?;
+            assert_eq!(state.borrow().time, monday_open);
+            assert_eq!(state.borrow().curr_tday, monday_open.date());
+            let clock = handle.stop().await?;
+            assert_eq!(clock.calendar().current_time(), &monday_open);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn manual_clock() -> Result<()> {
+        // 模拟周五白天到下周一白天, 每次推进到下一个状态切换时刻
+        let friday = make_date(2024, 9, 27);
+        let monday = make_date(2024, 9, 30);
+        let clock = Arc::new(ManualClock::new(date_at_hms(&friday, 14, 0, 0)));
+        let mut calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+        calendar.set_clock(clock.clone());
+        calendar.refresh(false)?;
+        assert!(calendar.is_trading());
+
+        let mut events = Vec::new();
+        while calendar.now() < date_at_hms(&monday, 10, 0, 0) {
+            let transition = calendar.next_transition(&calendar.now(), true)?;
+            clock.set(transition.at);
+            events.extend(calendar.refresh(false)?);
+        }
+        let started = events
+            .iter()
+            .filter(|e| **e == CalendarEvent::TradingStarted)
+            .count();
+        let stopped = events
+            .iter()
+            .filter(|e| **e == CalendarEvent::TradingStopped)
+            .count();
+        // 周五日盘收盘, 周五夜盘, 周一日盘
+        assert_eq!((started, stopped), (2, 3));
+        assert_eq!(calendar.current_tday(), &monday);
+        assert_eq!(calendar.now(), date_at_hms(&monday, 15, 30, 1));
+
+        clock.advance_secs(-60);
+        assert_eq!(calendar.now(), date_at_hms(&monday, 15, 29, 1));
+        let fixed = FixedClock(date_at_hms(&monday, 9, 0, 0));
+        assert_eq!(fixed.now(), fixed.now());
+        Ok(())
+    }
+
+    #[test]
+    fn replay_clock() -> Result<()> {
+        let friday = make_date(2024, 9, 27);
+        // 10万倍速, 半小时只需要约18毫秒
+        let start = date_at_hms(&friday, 15, 0, 0);
+        for speed in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(ReplayClock::new(start, speed).is_err());
+        }
+        let clock = ReplayClock::new(start, 100_000.0)?;
+        assert!(clock.now() >= date_at_hms(&friday, 15, 0, 0));
+        assert_eq!(
+            clock.real_duration(1_000_000_000_000),
+            Duration::from_millis(10)
+        );
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        rt.block_on(async {
+            let mut calendar = get_buildin_calendar(Some(make_date(2024, 1, 2)))?;
+            calendar.set_clock(Arc::new(clock));
+            let handle = CalendarClock::new(calendar, 16).spawn();
+            let mut events = handle.subscribe();
+            let mut stopped = false;
+            while !stopped {
+                let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await??;
+                stopped = event == CalendarEvent::TradingStopped;
+            }
+            let clock = handle.stop().await?;
+            assert!(clock.calendar().current_time() >= &date_at_hms(&friday, 15, 30, 1));
+            assert!(!clock.calendar().is_trading());
+            Ok(())
+        })
+    }
+}
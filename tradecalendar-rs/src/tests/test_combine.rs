@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{
+        CalendarSetOp, Exchange, combine_calendars, difference_calendars, get_buildin_registry,
+        intersect_calendars, union_calendars,
+    };
+
+    #[test]
+    fn calendar_set_ops() -> Result<()> {
+        let registry = get_buildin_registry(Some(make_date(2023, 1, 1)))?;
+        let sse = registry.get(Exchange::SSE)?;
+        let hkex = registry.get(Exchange::HKEX)?;
+        // 2024-03-29耶稣受难节, 香港休市; 2024-10-03国庆假期, 内地休市
+        let good_friday = make_date(2024, 3, 29);
+        let national_day = make_date(2024, 10, 3);
+
+        let both = intersect_calendars(sse, hkex)?;
+        assert_eq!(both.min_date(), Some(&make_date(2023, 1, 1)));
+        assert!(!both.is_trading_day(&good_friday)?);
+        assert!(!both.is_trading_day(&national_day)?);
+        assert_eq!(
+            both.get_next_trading_day(&make_date(2024, 3, 28), 1)?.date,
+            make_date(2024, 4, 2)
+        );
+        assert_eq!(
+            both.get_next_trading_day(&make_date(2024, 9, 30), 1)?.date,
+            make_date(2024, 10, 8)
+        );
+
+        let any = union_calendars(sse, hkex)?;
+        assert!(any.is_trading_day(&good_friday)?);
+        assert!(any.is_trading_day(&national_day)?);
+        assert!(!any.is_trading_day(&make_date(2024, 10, 1))?);
+        assert_eq!(
+            any.get_next_trading_day(&make_date(2024, 9, 30), 1)?.date,
+            make_date(2024, 10, 2)
+        );
+
+        // 内地开市而香港休市的日子
+        let only_sse = difference_calendars(sse, hkex)?;
+        assert!(only_sse.is_trading_day(&good_friday)?);
+        assert!(!only_sse.is_trading_day(&national_day)?);
+        assert!(!only_sse.is_trading_day(&make_date(2024, 3, 28))?);
+        assert!(only_sse.get_next_trading_day(&good_friday, 1)?.date > good_friday);
+
+        // 夜盘: 上期所、大商所都有夜盘的才保留, 中金所没有夜盘
+        let friday = make_date(2024, 9, 27);
+        let shfe = registry.get(Exchange::SHFE)?;
+        let dce = registry.get(Exchange::DCE)?;
+        let cffex = registry.get(Exchange::CFFEX)?;
+        let night = combine_calendars(shfe, dce, CalendarSetOp::Intersection)?;
+        assert!(night.get_date_detail(&friday).unwrap().night);
+        assert!(
+            night
+                .get_date_detail(&make_date(2024, 9, 28))
+                .unwrap()
+                .morning
+        );
+        let no_night = intersect_calendars(shfe, cffex)?;
+        assert!(!no_night.get_date_detail(&friday).unwrap().night);
+        // 节前最后一天没有夜盘
+        let holiday_eve = make_date(2024, 9, 30);
+        assert!(!night.get_date_detail(&holiday_eve).unwrap().night);
+
+        // 并集: 只有香港交易的日子, 前一天按上期所常规恢复夜盘, 当天有凌晨盘
+        let shfe_hk = union_calendars(shfe, hkex)?;
+        let eve = shfe_hk.get_date_detail(&holiday_eve).unwrap();
+        assert_eq!(eve.next, make_date(2024, 10, 2));
+        assert!(!eve.night);
+        let oct2 = shfe_hk.get_date_detail(&make_date(2024, 10, 2)).unwrap();
+        assert!(oct2.trading && oct2.night && !oct2.morning);
+        let oct3 = shfe_hk.get_date_detail(&national_day).unwrap();
+        assert!(oct3.trading && oct3.morning && oct3.night);
+        // 两者都交易的日子不变
+        let detail = shfe_hk.get_date_detail(&friday).unwrap();
+        assert!(detail.trading && detail.night);
+        assert!(
+            shfe_hk
+                .get_date_detail(&make_date(2024, 9, 28))
+                .unwrap()
+                .morning
+        );
+        Ok(())
+    }
+}
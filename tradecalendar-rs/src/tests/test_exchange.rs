@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{
+        Exchange, ExchangeTimeZone, HolidayTable, SessionTemplate, get_buildin_registry,
+        tradingdays_for_cfets,
+    };
+
+    #[test]
+    fn exchange_night() -> Result<()> {
+        let registry = get_buildin_registry(Some(make_date(2013, 1, 1)))?;
+        assert_eq!(registry.exchanges().len(), Exchange::ALL.len());
+
+        // 2013-07-05(周五)上期所第一次夜盘, 大商所还没有
+        let friday = make_date(2013, 7, 5);
+        let saturday = make_date(2013, 7, 6);
+        let shfe = registry.get(Exchange::SHFE)?;
+        assert!(shfe.get_date_detail(&friday).unwrap().night);
+        assert!(shfe.get_date_detail(&saturday).unwrap().morning);
+        let dce = registry.get(Exchange::DCE)?;
+        assert!(!dce.get_date_detail(&friday).unwrap().night);
+        assert!(!dce.get_date_detail(&saturday).unwrap().morning);
+
+        // 中金所、广期所从来没有夜盘
+        let day = make_date(2024, 9, 26);
+        assert!(
+            registry
+                .get(Exchange::DCE)?
+                .get_date_detail(&day)
+                .unwrap()
+                .night
+        );
+        assert!(
+            !registry
+                .get(Exchange::CFFEX)?
+                .get_date_detail(&day)
+                .unwrap()
+                .night
+        );
+        assert!(
+            !registry
+                .get(Exchange::GFEX)?
+                .get_date_detail(&day)
+                .unwrap()
+                .night
+        );
+
+        // 周五夜里, 大商所属于下周一, 中金所则取决于method
+        let datetime = date_at_hms(&make_date(2024, 9, 27), 21, 30, 0);
+        let monday = make_date(2024, 9, 30);
+        let tday = registry.trading_day_from_datetime(
+            Exchange::DCE,
+            &datetime,
+            NotTradingSearchMethod::Prev,
+            &SessionTemplate::commodity(Some(make_time(23, 0, 0))),
+        )?;
+        assert_eq!(tday, monday);
+        let tday = registry.trading_day_from_datetime(
+            Exchange::CFFEX,
+            &datetime,
+            NotTradingSearchMethod::Prev,
+            &SessionTemplate::cffex_index(),
+        )?;
+        assert_eq!(tday, make_date(2024, 9, 27));
+
+        assert!(registry.is_trading_day(Exchange::SSE, &monday)?);
+        assert_eq!(
+            registry
+                .get_next_trading_day(Exchange::SZSE, &monday, 1)?
+                .date,
+            make_date(2024, 10, 8)
+        );
+        assert_eq!("sh".parse::<Exchange>()?, Exchange::SSE);
+        Ok(())
+    }
+
+    #[test]
+    fn cfets_calendar() -> Result<()> {
+        let registry = get_buildin_registry(Some(make_date(2025, 1, 1)))?;
+        let cfets = registry.get(Exchange::CFETS)?;
+        let friday = make_date(2026, 2, 13);
+        let makeup = make_date(2026, 2, 14);
+
+        // 调休上班的周六, 交易所休市, 银行间市场交易
+        assert!(!registry.is_trading_day(Exchange::SHFE, &makeup)?);
+        assert!(cfets.is_trading_day(&makeup)?);
+        assert_eq!(cfets.get_next_trading_day(&friday, 1)?.date, makeup);
+        assert_eq!(
+            cfets.get_next_trading_day(&makeup, 1)?.date,
+            make_date(2026, 2, 24)
+        );
+        assert_eq!(
+            registry
+                .get_next_trading_day(Exchange::SHFE, &friday, 1)?
+                .date,
+            make_date(2026, 2, 24)
+        );
+        assert_eq!(
+            cfets
+                .get_prev_trading_day(&make_date(2026, 10, 12), 1)?
+                .date,
+            make_date(2026, 10, 10)
+        );
+        // 没有夜盘
+        let detail = cfets.get_date_detail(&friday).unwrap();
+        assert!(detail.trading && !detail.night && !detail.morning);
+        // 往年的调休上班日
+        assert!(cfets.is_trading_day(&make_date(2025, 1, 26))?);
+        assert!(!registry.is_trading_day(Exchange::SHFE, &make_date(2025, 1, 26))?);
+        assert_eq!(
+            cfets.get_next_trading_day(&make_date(2025, 2, 7), 1)?.date,
+            make_date(2025, 2, 8)
+        );
+        assert_eq!(
+            cfets.get_next_trading_day(&make_date(2025, 9, 30), 1)?.date,
+            make_date(2025, 10, 9)
+        );
+        let registry_2009 = get_buildin_registry(None)?;
+        let cfets_2009 = registry_2009.get(Exchange::CFETS)?;
+        assert!(cfets_2009.is_trading_day(&make_date(2009, 1, 4))?);
+        // 没有调休数据的年份报错, 不会与交易所相同
+        let mut table = HolidayTable::new();
+        table.add_holiday(make_date(2026, 1, 1), "元旦");
+        let full_list = registry.get(Exchange::SHFE)?.get_full_day_list();
+        assert!(tradingdays_for_cfets(full_list, &table).is_err());
+        assert_eq!("ib".parse::<Exchange>()?, Exchange::CFETS);
+        Ok(())
+    }
+
+    #[test]
+    fn action_datetime() -> Result<()> {
+        let registry = get_buildin_registry(Some(make_date(2024, 1, 1)))?;
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let monday = make_date(2024, 9, 30);
+
+        // 周一交易日的夜盘在周五晚上, 凌晨盘在周六
+        let time = make_time(21, 30, 0);
+        assert_eq!(
+            registry.action_datetime(Exchange::DCE, &monday, &time)?,
+            date_at_hms(&friday, 21, 30, 0)
+        );
+        let time = make_time(1, 0, 0);
+        assert_eq!(
+            registry.action_datetime(Exchange::SHFE, &monday, &time)?,
+            date_at_hms(&saturday, 1, 0, 0)
+        );
+        let time = make_time(10, 0, 0);
+        assert_eq!(
+            registry.action_datetime(Exchange::DCE, &monday, &time)?,
+            date_at_hms(&monday, 10, 0, 0)
+        );
+        // 郑商所夜盘的TradingDay就是自然日
+        let time = make_time(21, 30, 0);
+        assert_eq!(
+            registry.action_datetime(Exchange::CZCE, &friday, &time)?,
+            date_at_hms(&friday, 21, 30, 0)
+        );
+
+        // 国庆节后第一个交易日没有夜盘
+        let after_holiday = make_date(2024, 10, 8);
+        assert!(
+            registry
+                .action_datetime(Exchange::SHFE, &after_holiday, &time)
+                .is_err()
+        );
+        assert!(
+            registry
+                .action_datetime(Exchange::SHFE, &saturday, &time)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exchange_time_zone() -> Result<()> {
+        let registry = get_buildin_registry(Some(make_date(2024, 1, 1)))?;
+        let session = SessionTemplate::commodity(Some(make_time(23, 0, 0)));
+        let friday = make_date(2024, 9, 27);
+        let monday = make_date(2024, 9, 30);
+
+        // UTC 13:30 即北京时间21:30, 周五夜盘属于周一
+        let nanos = datetime_to_timestamp_nanos(&date_at_hms(&friday, 13, 30, 0));
+        let tz = Exchange::SHFE.time_zone();
+        assert_eq!(tz.name(), "Asia/Shanghai");
+        assert_eq!(tz.from_utc_nanos(nanos), date_at_hms(&friday, 21, 30, 0));
+        assert_eq!(
+            registry.trading_day_from_utc_nanos(
+                Exchange::SHFE,
+                nanos,
+                NotTradingSearchMethod::Next,
+                &session
+            )?,
+            monday
+        );
+        let calendar = registry.get(Exchange::DCE)?;
+        assert_eq!(
+            calendar.trading_day_from_utc_nanos(
+                nanos,
+                ExchangeTimeZone::HongKong,
+                NotTradingSearchMethod::Next,
+                &session
+            )?,
+            monday
+        );
+
+        #[cfg(feature = "with-chrono")]
+        {
+            let input = chrono::DateTime::parse_from_rfc3339("2024-09-27T13:30:00Z")?;
+            assert_eq!(tz.from_zoned(&input), date_at_hms(&friday, 21, 30, 0));
+            let input = chrono::DateTime::parse_from_rfc3339("2024-09-27T09:30:00-04:00")?;
+            assert_eq!(
+                calendar.trading_day_from_zoned(
+                    &input,
+                    tz,
+                    NotTradingSearchMethod::Next,
+                    &session
+                )?,
+                monday
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{
+        Exchange, ExchangeTimeZone, easter_sunday, get_buildin_registry, hk_public_holidays,
+        hkex_buildin_overrides,
+    };
+
+    #[test]
+    fn hk_holidays() -> Result<()> {
+        assert_eq!(easter_sunday(2024), make_date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), make_date(2025, 4, 20));
+
+        let dates = |year| -> Result<Vec<String>> {
+            Ok(hk_public_holidays(year)?
+                .iter()
+                .map(|h| h.date.to_string())
+                .collect())
+        };
+        // 年初二是周日, 初四补假
+        assert_eq!(
+            dates(2024)?,
+            vec![
+                "2024-01-01",
+                "2024-02-10",
+                "2024-02-12",
+                "2024-02-13",
+                "2024-03-29",
+                "2024-03-30",
+                "2024-04-01",
+                "2024-04-04",
+                "2024-05-01",
+                "2024-05-15",
+                "2024-06-10",
+                "2024-07-01",
+                "2024-09-18",
+                "2024-10-01",
+                "2024-10-11",
+                "2024-12-25",
+                "2024-12-26",
+            ]
+        );
+        let names: Vec<String> = hk_public_holidays(2024)?
+            .into_iter()
+            .filter(|h| h.date >= make_date(2024, 2, 10) && h.date <= make_date(2024, 2, 13))
+            .map(|h| h.name)
+            .collect();
+        assert_eq!(names, vec!["农历年初一", "农历年初三", "农历年初四"]);
+        // 元旦、年初一、国庆日是周日, 中秋节翌日是周六
+        assert_eq!(
+            dates(2023)?,
+            vec![
+                "2023-01-02",
+                "2023-01-23",
+                "2023-01-24",
+                "2023-01-25",
+                "2023-04-05",
+                "2023-04-07",
+                "2023-04-08",
+                "2023-04-10",
+                "2023-05-01",
+                "2023-05-26",
+                "2023-06-22",
+                "2023-07-01",
+                "2023-09-30",
+                "2023-10-02",
+                "2023-10-23",
+                "2023-12-25",
+                "2023-12-26",
+            ]
+        );
+        let name_at = |date| -> Result<Option<String>> {
+            Ok(hk_public_holidays(2023)?
+                .into_iter()
+                .find(|h| h.date == date)
+                .map(|h| h.name))
+        };
+        assert_eq!(name_at(make_date(2023, 1, 2))?.as_deref(), Some("元旦翌日"));
+        assert_eq!(
+            name_at(make_date(2023, 1, 25))?.as_deref(),
+            Some("农历年初四")
+        );
+        assert_eq!(
+            name_at(make_date(2023, 1, 24))?.as_deref(),
+            Some("农历年初三")
+        );
+        // 中秋节翌日(2022-09-11)是周日, 顺延至周一
+        let mid_autumn: Vec<(String, String)> = hk_public_holidays(2022)?
+            .into_iter()
+            .filter(|h| h.date >= make_date(2022, 9, 1) && h.date <= make_date(2022, 9, 30))
+            .map(|h| (h.date.to_string(), h.name))
+            .collect();
+        assert_eq!(
+            mid_autumn,
+            vec![("2022-09-12".to_string(), "中秋节后第二日".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hkex_and_stock_connect() -> Result<()> {
+        let mut registry = get_buildin_registry(Some(make_date(2023, 1, 1)))?;
+        assert_eq!(Exchange::HKEX.time_zone(), ExchangeTimeZone::HongKong);
+        let hkex = registry.get(Exchange::HKEX)?;
+        assert_eq!(hkex.min_date(), Some(&make_date(2023, 1, 1)));
+        // 内地国庆假期, 香港照常交易
+        assert!(hkex.is_trading_day(&make_date(2024, 10, 3))?);
+        assert!(!hkex.is_trading_day(&make_date(2024, 10, 1))?);
+        assert_eq!(
+            hkex.get_next_trading_day(&make_date(2024, 3, 28), 1)?.date,
+            make_date(2024, 4, 2)
+        );
+
+        let connect = registry.stock_connect()?;
+        // 两地都开市
+        assert!(connect.is_trading_day(&make_date(2024, 9, 27))?);
+        // 内地休市
+        assert!(!connect.is_trading_day(&make_date(2024, 10, 3))?);
+        // 香港复活节前一天, 两地都开市, 但次日香港休市无法交收
+        assert!(registry.is_trading_day(Exchange::SSE, &make_date(2024, 3, 28))?);
+        assert!(registry.is_trading_day(Exchange::HKEX, &make_date(2024, 3, 28))?);
+        assert!(!connect.is_trading_day(&make_date(2024, 3, 28))?);
+        assert_eq!(
+            connect
+                .get_next_trading_day(&make_date(2024, 3, 27), 1)?
+                .date,
+            make_date(2024, 4, 2)
+        );
+        let detail = connect.get_date_detail(&make_date(2024, 9, 27)).unwrap();
+        assert!(!detail.night && !detail.morning);
+
+        // 政府临时宣布的假期
+        let registry_2015 = get_buildin_registry(Some(make_date(2015, 1, 1)))?;
+        assert!(!registry_2015.is_trading_day(Exchange::HKEX, &make_date(2015, 9, 3))?);
+        assert_eq!(
+            registry_2015
+                .get_next_trading_day(Exchange::HKEX, &make_date(2015, 9, 2), 1)?
+                .date,
+            make_date(2015, 9, 4)
+        );
+        assert_eq!(hkex_buildin_overrides().items().len(), 1);
+        let registry_2022 = get_buildin_registry(Some(make_date(2022, 1, 1)))?;
+        assert!(registry_2022.is_trading_day(Exchange::HKEX, &make_date(2022, 9, 9))?);
+        assert!(!registry_2022.is_trading_day(Exchange::HKEX, &make_date(2022, 9, 12))?);
+
+        // 2023-09-08黑雨停市
+        let black_rainstorm = make_date(2023, 9, 8);
+        registry.close_days(Exchange::HKEX, &[black_rainstorm])?;
+        assert!(!registry.is_trading_day(Exchange::HKEX, &black_rainstorm)?);
+        assert_eq!(
+            registry
+                .get_next_trading_day(Exchange::HKEX, &make_date(2023, 9, 7), 1)?
+                .date,
+            make_date(2023, 9, 11)
+        );
+        assert!(!registry.stock_connect()?.is_trading_day(&black_rainstorm)?);
+        Ok(())
+    }
+}
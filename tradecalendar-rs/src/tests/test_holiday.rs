@@ -0,0 +1,300 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{
+        DayKind, HolidayTable, estimate_tradingdays, get_buildin_calendar, get_csv_calendar,
+        holidays_to_tradingdays, is_weekend, load_holidays_buildin, load_tradingdays_buildin,
+        lunar_to_solar, predict_holidays, qingming, tradingdays_to_calendar,
+    };
+
+    #[test]
+    fn lunar_dates() -> Result<()> {
+        // 春节
+        assert_eq!(lunar_to_solar(2024, 1, 1, false)?, make_date(2024, 2, 10));
+        assert_eq!(lunar_to_solar(2025, 1, 1, false)?, make_date(2025, 1, 29));
+        assert_eq!(lunar_to_solar(2026, 1, 1, false)?, make_date(2026, 2, 17));
+        // 端午, 中秋
+        assert_eq!(lunar_to_solar(2024, 5, 5, false)?, make_date(2024, 6, 10));
+        assert_eq!(lunar_to_solar(2024, 8, 15, false)?, make_date(2024, 9, 17));
+        assert_eq!(lunar_to_solar(2025, 8, 15, false)?, make_date(2025, 10, 6));
+        // 2025年闰六月
+        assert_eq!(lunar_to_solar(2025, 6, 1, true)?, make_date(2025, 7, 25));
+        assert!(lunar_to_solar(2025, 5, 1, true).is_err());
+        assert!(lunar_to_solar(2024, 13, 1, false).is_err());
+        assert!(lunar_to_solar(1999, 1, 1, false).is_err());
+
+        assert_eq!(qingming(2024)?, make_date(2024, 4, 4));
+        assert_eq!(qingming(2025)?, make_date(2025, 4, 4));
+        assert_eq!(qingming(2026)?, make_date(2026, 4, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn predict_holidays_2026() -> Result<()> {
+        let published = load_holidays_buildin()?.holidays_in(2026);
+        let predicted = predict_holidays(2026)?;
+        assert_eq!(
+            predicted.iter().map(|h| h.date).collect::<Vec<_>>(),
+            published.iter().map(|h| h.date).collect::<Vec<_>>()
+        );
+        assert_eq!(predicted[0].name, "元旦");
+        Ok(())
+    }
+
+    #[test]
+    fn holiday_blocks() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2025, 1, 1)))?;
+        assert_eq!(
+            calendar.holiday_at(&make_date(2026, 2, 16)).as_deref(),
+            Some("除夕")
+        );
+        assert_eq!(calendar.holiday_at(&make_date(2026, 2, 21)), None);
+        assert_eq!(calendar.holiday_at(&make_date(2026, 2, 24)), None);
+        let holidays = calendar.holidays_in(2026);
+        assert_eq!(holidays.len(), 19);
+        assert_eq!(holidays[0].name, "元旦");
+
+        // 春节连同前后周末共9天, 除夕并入春节
+        let block = calendar
+            .current_holiday_block(&make_date(2026, 2, 21))
+            .unwrap();
+        assert_eq!(block.name, "春节");
+        assert_eq!(block.first_day, make_date(2026, 2, 15));
+        assert_eq!(block.last_day, make_date(2026, 2, 23));
+        assert_eq!(block.length, 9);
+        let block = calendar
+            .current_holiday_block(&make_date(2026, 10, 1))
+            .unwrap();
+        assert_eq!(
+            (block.name.as_str(), block.last_day, block.length),
+            ("国庆", make_date(2026, 10, 7), 7)
+        );
+        // 交易日、调休上班日、普通周末都不在假期中
+        assert!(
+            calendar
+                .current_holiday_block(&make_date(2026, 2, 24))
+                .is_none()
+        );
+        assert!(
+            calendar
+                .current_holiday_block(&make_date(2026, 2, 14))
+                .is_none()
+        );
+        assert!(
+            calendar
+                .current_holiday_block(&make_date(2026, 3, 7))
+                .is_none()
+        );
+
+        // 往年的数据来自holidays_history.csv
+        let block = calendar
+            .current_holiday_block(&make_date(2025, 5, 3))
+            .unwrap();
+        assert_eq!(block.name, "劳动节");
+        assert_eq!(block.first_day, make_date(2025, 5, 1));
+        assert_eq!(block.length, 5);
+        assert_eq!(
+            calendar.holiday_at(&make_date(2025, 10, 6)).as_deref(),
+            Some("中秋")
+        );
+
+        // 没有holidays数据时按日历推断, 名称为空
+        let mut calendar = calendar;
+        calendar.set_holiday_table(None);
+        let block = calendar
+            .current_holiday_block(&make_date(2025, 5, 3))
+            .unwrap();
+        assert_eq!(block.name, "");
+        assert_eq!(block.first_day, make_date(2025, 5, 1));
+        assert_eq!(block.length, 5);
+        assert_eq!(calendar.holidays_in(2025)[0].date, make_date(2025, 1, 1));
+        assert_eq!(
+            calendar.holiday_at(&make_date(2025, 5, 5)).as_deref(),
+            Some("")
+        );
+
+        // 从外部csv文件构造的日历没有节假日数据, 由调用方设置
+        let mut calendar = get_csv_calendar("../calendar.csv", Some(make_date(2025, 1, 1)))?;
+        assert!(calendar.holiday_table().is_none());
+        assert_eq!(
+            calendar.holiday_at(&make_date(2026, 2, 16)).as_deref(),
+            Some("")
+        );
+        calendar.set_holiday_table(Some(load_holidays_buildin()?));
+        assert_eq!(
+            calendar.holiday_at(&make_date(2026, 2, 16)).as_deref(),
+            Some("除夕")
+        );
+        assert_eq!(calendar.holidays_in(2026).len(), 19);
+        Ok(())
+    }
+
+    #[test]
+    fn pre_and_post_holiday() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        // 国庆前最后一个交易日, 休市7天
+        let eve = make_date(2024, 9, 30);
+        assert_eq!(calendar.closed_days_after(&eve)?, 7);
+        assert!(calendar.is_pre_holiday(&eve, 4)?);
+        assert!(calendar.is_post_holiday(&make_date(2024, 10, 8), 4)?);
+        assert!(!calendar.is_post_holiday(&make_date(2024, 10, 9), 4)?);
+        // 普通周末
+        let friday = make_date(2024, 9, 27);
+        assert_eq!(calendar.closed_days_after(&friday)?, 2);
+        assert!(calendar.is_pre_holiday(&friday, 2)?);
+        assert!(!calendar.is_pre_holiday(&friday, 3)?);
+        assert!(!calendar.is_post_holiday(&make_date(2024, 9, 30), 3)?);
+        assert!(calendar.closed_days_after(&make_date(2024, 10, 1)).is_err());
+        assert!(!calendar.is_pre_holiday(&make_date(2024, 10, 1), 3)?);
+
+        // 中秋(9月15~17日)之后, 下一个超过3天的休市是国庆
+        let day = make_date(2024, 9, 18);
+        assert!(calendar.is_post_holiday(&day, 4)?);
+        assert_eq!(calendar.next_pre_holiday(&day, 4).unwrap().date, eve);
+        assert_eq!(calendar.trading_days_until_closure(&day, 4), Some(8));
+        assert_eq!(calendar.trading_days_until_closure(&eve, 4), Some(0));
+        // 周末从下周一算起
+        assert_eq!(
+            calendar.trading_days_until_closure(&make_date(2024, 9, 29), 4),
+            Some(0)
+        );
+        assert_eq!(
+            calendar
+                .next_pre_holiday(&make_date(2024, 9, 10), 4)
+                .unwrap()
+                .date,
+            make_date(2024, 9, 13)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn day_kind() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2025, 1, 1)))?;
+        let kind = |y, m, d| calendar.day_kind(&make_date(y, m, d));
+        // 调休上班的周日, 交易所休市, 银行营业
+        assert_eq!(kind(2026, 1, 4), DayKind::MakeupWorkday);
+        assert!(kind(2026, 1, 4).is_working_day());
+        assert!(!kind(2026, 1, 4).is_exchange_open());
+        assert_eq!(
+            kind(2026, 2, 16),
+            DayKind::Holiday {
+                name: "除夕".to_string()
+            }
+        );
+        assert!(!kind(2026, 2, 16).is_working_day());
+        assert_eq!(kind(2026, 1, 10), DayKind::Weekend);
+        assert_eq!(kind(2026, 1, 5), DayKind::Weekday);
+        assert_eq!(
+            kind(2025, 10, 8),
+            DayKind::Holiday {
+                name: "国庆".to_string()
+            }
+        );
+        assert_eq!(kind(2025, 9, 28), DayKind::MakeupWorkday);
+        // 没有holidays数据时按日历推断, 无法识别调休上班日
+        let mut no_table = get_buildin_calendar(Some(make_date(2025, 1, 1)))?;
+        no_table.set_holiday_table(None);
+        assert_eq!(
+            no_table.day_kind(&make_date(2025, 10, 8)),
+            DayKind::Holiday {
+                name: String::new()
+            }
+        );
+        assert_eq!(no_table.day_kind(&make_date(2025, 9, 28)), DayKind::Weekend);
+
+        // 兼容没有_kind列的文件, 写出时包含_kind列
+        let table = HolidayTable::load_csv_read(
+            "_date,_name\n2026-01-01,元旦\n2026-01-03,元旦\n".as_bytes(),
+        )?;
+        assert_eq!(table.holidays().len(), 1);
+        assert!(table.contains_year(2026));
+        assert!(!table.contains_year(2027));
+        let mut table = load_holidays_buildin()?;
+        let makeup_2026 = table
+            .makeup_days()
+            .into_iter()
+            .filter(|date| date_ymd(date).0 == 2026)
+            .count();
+        assert_eq!(makeup_2026, 6);
+        // 包含2009年以来的往年数据
+        assert_eq!(table.years(), (2009..=2026).collect::<Vec<_>>());
+        let mut buf = Vec::new();
+        table.write_csv(&mut buf)?;
+        assert!(buf.starts_with(b"_date,_name,_kind\n2009-01-01,"));
+        assert_eq!(HolidayTable::load_csv_read(&buf[..])?, table);
+        // 周六周日不能添加为节假日
+        table.add_holiday(make_date(2026, 1, 4), "test");
+        assert_eq!(
+            table.day_kind(&make_date(2026, 1, 4)),
+            DayKind::MakeupWorkday
+        );
+        table.add_holiday(make_date(2026, 1, 5), "test");
+        assert_ne!(HolidayTable::load_csv_read(&buf[..])?, table);
+        assert!(
+            HolidayTable::load_csv_read("_date,_name,_kind\n2026-01-01,元旦,x\n".as_bytes())
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn buildin_holidays_match_calendar() -> Result<()> {
+        // 内置的节假日与calendar.csv一致: 周一至周五休市的都是节假日, 调休上班日交易所都休市
+        let table = load_holidays_buildin()?;
+        for day in load_tradingdays_buildin()? {
+            let kind = table.day_kind(&day.date);
+            let is_holiday = matches!(kind, DayKind::Holiday { .. });
+            assert_eq!(
+                is_holiday,
+                !day.trading && !is_weekend(&day.date),
+                "{}",
+                day.date
+            );
+            if kind == DayKind::MakeupWorkday {
+                assert!(!day.trading, "{}", day.date);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn estimated_calendar() -> Result<()> {
+        let days = estimate_tradingdays(2027)?;
+        // 上年最后一个交易日及全年每一天
+        assert_eq!(days.len(), 366);
+        assert_eq!(days[0].date, make_date(2026, 12, 31));
+        assert_eq!(days[0].next, make_date(2027, 1, 4));
+        // 2027年春节: 2月6日(周六), 除夕2月5日
+        let feb04 = days
+            .iter()
+            .find(|d| d.date == make_date(2027, 2, 4))
+            .expect("2027-02-04");
+        assert!(feb04.trading);
+        assert!(!feb04.night);
+        assert_eq!(feb04.next, make_date(2027, 2, 15));
+        // 输入为空或太短时返回错误, 不会panic
+        assert!(holidays_to_tradingdays(&[]).is_err());
+        assert!(tradingdays_to_calendar(&[make_date(2027, 1, 4)]).is_err());
+
+        // 内置日历数据截止到2026-12-31, 之后使用估算的日历
+        let mut calendar = get_buildin_calendar(Some(make_date(2026, 12, 1)))?;
+        let day = make_date(2027, 2, 4);
+        assert!(calendar.is_estimated(&day));
+        assert!(!calendar.is_estimated(&make_date(2026, 12, 31)));
+        let (_, curr_tday, _, _, opt_err) =
+            calendar.time_changed(&date_at_hms(&day, 10, 0, 0), true)?;
+        assert_eq!(curr_tday, day);
+        assert!(opt_err.expect("estimated").contains("estimated"));
+        assert!(calendar.is_trading());
+        assert_eq!(calendar.next_tday(), &make_date(2027, 2, 15));
+        // 节前最后一天没有夜盘
+        calendar.time_changed(&date_at_hms(&day, 21, 30, 0), true)?;
+        assert!(!calendar.is_trading());
+        assert_eq!(calendar.current_tday(), &day);
+        Ok(())
+    }
+}
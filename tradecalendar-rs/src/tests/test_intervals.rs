@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::{TradingPhase, get_buildin_calendar};
+
+    #[test]
+    fn session_intervals() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let monday = make_date(2024, 9, 30);
+
+        // 周一的交易日, 包含周五夜盘和周六凌晨盘
+        let intervals = calendar.session_intervals(&monday, "au2412")?;
+        assert_eq!(
+            intervals,
+            vec![
+                (
+                    date_at_hms(&friday, 21, 0, 0),
+                    date_at_hms(&saturday, 0, 0, 0)
+                ),
+                (
+                    date_at_hms(&saturday, 0, 0, 0),
+                    date_at_hms(&saturday, 2, 30, 0)
+                ),
+                (
+                    date_at_hms(&monday, 9, 0, 0),
+                    date_at_hms(&monday, 10, 15, 0)
+                ),
+                (
+                    date_at_hms(&monday, 10, 30, 0),
+                    date_at_hms(&monday, 11, 30, 0)
+                ),
+                (
+                    date_at_hms(&monday, 13, 30, 0),
+                    date_at_hms(&monday, 15, 0, 0)
+                ),
+            ]
+        );
+        let intervals = calendar.session_intervals(&monday, "rb2501")?;
+        assert_eq!(intervals.len(), 4);
+        assert_eq!(
+            intervals[0],
+            (
+                date_at_hms(&friday, 21, 0, 0),
+                date_at_hms(&friday, 23, 0, 0)
+            )
+        );
+        let intervals = calendar.session_intervals(&monday, "IF2410")?;
+        assert_eq!(intervals.len(), 2);
+        assert_eq!(intervals[0].0, date_at_hms(&monday, 9, 30, 0));
+
+        // 国庆节前最后一天没有夜盘, 节后第一天只有日盘
+        let after_holiday = make_date(2024, 10, 8);
+        let intervals = calendar.session_intervals(&after_holiday, "au2412")?;
+        assert_eq!(intervals.len(), 3);
+        assert_eq!(intervals[0].0, date_at_hms(&after_holiday, 9, 0, 0));
+        // 节后第一天晚上恢复夜盘
+        let intervals = calendar.session_intervals(&make_date(2024, 10, 9), "cu2412")?;
+        assert_eq!(
+            intervals[1],
+            (
+                date_at_hms(&make_date(2024, 10, 9), 0, 0, 0),
+                date_at_hms(&make_date(2024, 10, 9), 1, 0, 0)
+            )
+        );
+
+        assert!(calendar.session_intervals(&saturday, "au2412").is_err());
+        assert!(calendar.session_intervals(&monday, "unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn trading_phase() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let sunday = make_date(2024, 9, 29);
+
+        let phase = |date: &MyDateType, h, m, product| {
+            calendar.phase_at(&date_at_hms(date, h, m, 0), product)
+        };
+        // 有夜盘的品种, 日盘开盘前没有集合竞价
+        assert_eq!(phase(&friday, 8, 59, "rb2501")?, TradingPhase::Closed);
+        // 节后第一天没有夜盘, 日盘开盘前集合竞价
+        assert_eq!(
+            phase(&make_date(2024, 10, 8), 8, 59, "rb2501")?,
+            TradingPhase::PreOpenAuction
+        );
+        assert_eq!(
+            phase(&friday, 9, 0, "rb2501")?,
+            TradingPhase::ContinuousMorning
+        );
+        assert_eq!(phase(&friday, 10, 20, "rb2501")?, TradingPhase::Break);
+        assert_eq!(phase(&friday, 12, 0, "rb2501")?, TradingPhase::LunchBreak);
+        assert_eq!(
+            phase(&friday, 14, 0, "rb2501")?,
+            TradingPhase::ContinuousAfternoon
+        );
+        assert_eq!(phase(&friday, 15, 0, "rb2501")?, TradingPhase::Closed);
+        assert_eq!(
+            phase(&friday, 20, 59, "rb2501")?,
+            TradingPhase::PreOpenAuction
+        );
+        assert_eq!(
+            phase(&friday, 22, 0, "rb2501")?,
+            TradingPhase::ContinuousNight
+        );
+        assert_eq!(phase(&friday, 23, 30, "rb2501")?, TradingPhase::Closed);
+        assert_eq!(
+            phase(&saturday, 1, 0, "au2412")?,
+            TradingPhase::ContinuousNight
+        );
+        assert_eq!(phase(&saturday, 1, 0, "rb2501")?, TradingPhase::Holiday);
+        assert_eq!(phase(&sunday, 10, 0, "rb2501")?, TradingPhase::Holiday);
+        // 股指期货没有夜盘, 日盘开盘前集合竞价, 没有小节休息, 午休11:30~13:00
+        assert_eq!(
+            phase(&friday, 9, 28, "IF2410")?,
+            TradingPhase::PreOpenAuction
+        );
+        assert_eq!(
+            phase(&friday, 10, 20, "IF2410")?,
+            TradingPhase::ContinuousMorning
+        );
+        assert_eq!(phase(&friday, 12, 0, "IF2410")?, TradingPhase::LunchBreak);
+        assert_eq!(phase(&friday, 21, 0, "IF2410")?, TradingPhase::Closed);
+        // 国庆节
+        assert_eq!(
+            phase(&make_date(2024, 10, 2), 10, 0, "rb2501")?,
+            TradingPhase::Holiday
+        );
+        assert!(phase(&friday, 10, 0, "unknown").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{CalendarOverrides, Exchange, OverrideKind, get_buildin_registry};
+
+    #[test]
+    fn overrides_layer() -> Result<()> {
+        let csv = "_date,_kind,_reason\n\
+                   2024-09-24,cancel_night,系统升级\n\
+                   2024-09-26,close,测试停市\n\
+                   2030-01-02,close,超出日历范围\n";
+        let overrides = CalendarOverrides::load_csv_read(csv.as_bytes())?;
+        assert_eq!(overrides.items().len(), 3);
+        let at = overrides.overrides_at(&make_date(2024, 9, 26));
+        assert_eq!(at.len(), 1);
+        assert_eq!(at[0].kind, OverrideKind::Close);
+        assert_eq!(at[0].reason, "测试停市");
+
+        let mut registry = get_buildin_registry(Some(make_date(2024, 1, 1)))?;
+        let applied = registry.apply_overrides(Exchange::DCE, &overrides)?;
+        assert_eq!(applied, 2);
+        let dce = registry.get(Exchange::DCE)?;
+        // 取消夜盘, 次日没有早盘
+        assert!(!dce.get_date_detail(&make_date(2024, 9, 24)).unwrap().night);
+        assert!(
+            !dce.get_date_detail(&make_date(2024, 9, 25))
+                .unwrap()
+                .morning
+        );
+        // 停市, 前一交易日的next和夜盘都要重新计算
+        let thursday = make_date(2024, 9, 26);
+        assert!(!dce.is_trading_day(&thursday)?);
+        let wednesday = dce.get_date_detail(&make_date(2024, 9, 25)).unwrap();
+        assert_eq!(wednesday.next, make_date(2024, 9, 27));
+        assert!(!wednesday.night);
+        assert!(dce.get_date_detail(&make_date(2024, 9, 27)).unwrap().night);
+        // 其他交易所不受影响
+        assert!(registry.is_trading_day(Exchange::SHFE, &thursday)?);
+        // 生效的调整记录保留在日历上
+        let applied = dce.overrides();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[1].date, thursday);
+        assert_eq!(applied[1].kind, OverrideKind::Close);
+        assert_eq!(applied[1].reason, "测试停市");
+        assert!(registry.get(Exchange::SHFE)?.overrides().is_empty());
+        registry.close_days(Exchange::DCE, &[make_date(2024, 9, 20)])?;
+        assert_eq!(registry.get(Exchange::DCE)?.overrides().len(), 2);
+
+        // 临时开市, 恢复夜盘
+        let mut list = registry.get(Exchange::DCE)?.get_full_day_list().clone();
+        let mut restore = CalendarOverrides::new();
+        restore
+            .open_day(thursday, "恢复")
+            .add_night(make_date(2024, 9, 25), "恢复");
+        assert_eq!(restore.apply(&mut list).len(), 2);
+        let calendar = StaticCalendar::new(list)?;
+        assert!(calendar.is_trading_day(&thursday)?);
+        assert!(calendar.get_date_detail(&thursday).unwrap().morning);
+
+        // 只有临时开市, 前一交易日及开市当天按常规恢复夜盘
+        let mut list = registry.get(Exchange::DCE)?.get_full_day_list().clone();
+        let mut open = CalendarOverrides::new();
+        open.open_day(thursday, "恢复");
+        let applied = open.apply(&mut list);
+        assert_eq!(applied[0].reason, "恢复");
+        let calendar = StaticCalendar::new(list)?;
+        let detail = |date| calendar.get_date_detail(&date).unwrap();
+        assert!(detail(make_date(2024, 9, 25)).night);
+        assert!(detail(thursday).morning);
+        assert!(detail(thursday).night);
+        assert!(detail(make_date(2024, 9, 27)).morning);
+        // 节前最后一天不受影响, 取消的夜盘不会恢复
+        assert!(!detail(make_date(2024, 9, 30)).night);
+        assert!(!detail(make_date(2024, 9, 24)).night);
+
+        // 节假日临时开市, 与节前最后一天相邻
+        let mut list = registry.get(Exchange::DCE)?.get_full_day_list().clone();
+        let mut open = CalendarOverrides::new();
+        open.open_day(make_date(2024, 10, 1), "测试");
+        open.apply(&mut list);
+        let calendar = StaticCalendar::new(list)?;
+        let eve = calendar.get_date_detail(&make_date(2024, 9, 30)).unwrap();
+        assert_eq!(eve.next, make_date(2024, 10, 1));
+        assert!(eve.night);
+        let opened = calendar.get_date_detail(&make_date(2024, 10, 1)).unwrap();
+        assert!(opened.morning && !opened.night);
+
+        let mut out = Vec::new();
+        overrides.write_csv(&mut out)?;
+        assert_eq!(String::from_utf8(out)?, csv);
+        assert!(
+            CalendarOverrides::load_csv_read("_date,_kind\n2024-09-26,halt\n".as_bytes()).is_err()
+        );
+        Ok(())
+    }
+}
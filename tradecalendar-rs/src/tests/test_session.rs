@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::get_buildin_calendar;
+    use crate::jcswitch::*;
+    use crate::tradecalendar::*;
+    use crate::{EffectiveSession, Exchange, SessionHistory, SessionRegistry, SessionTemplate};
+
+    #[test]
+    fn session_template() -> Result<()> {
+        let registry = SessionRegistry::buildin();
+        let rb = registry.template("rb2501")?;
+        let cu = registry.template("CU")?;
+        let au = registry.template("au")?;
+        let tf = registry.template("TF2503")?;
+        assert_eq!(registry.exchange("SR501"), Some(Exchange::CZCE));
+        assert!(registry.template("xx").is_err());
+
+        // 2024-09-26(周四)
+        let thursday = make_date(2024, 9, 26);
+        let friday = make_date(2024, 9, 27);
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+
+        // 23:30, 螺纹已经收盘, 铜还在交易
+        let datetime = date_at_hms(&thursday, 23, 30, 0);
+        assert!(!calendar.is_in_session(&datetime, rb)?);
+        assert!(calendar.is_in_session(&datetime, cu)?);
+        // 10:20 商品小节休息, 国债期货在交易
+        let datetime = date_at_hms(&friday, 10, 20, 0);
+        assert!(!calendar.is_in_session(&datetime, rb)?);
+        assert!(calendar.is_in_session(&datetime, tf)?);
+        // 周六凌晨2:00, 黄金还在交易
+        let datetime = date_at_hms(&make_date(2024, 9, 28), 2, 0, 0);
+        assert!(calendar.is_in_session(&datetime, au)?);
+        assert!(!calendar.is_in_session(&datetime, cu)?);
+
+        // 15:10, 国债期货仍属当天, 商品期货则取决于method
+        let datetime = date_at_hms(&thursday, 15, 10, 0);
+        let method = NotTradingSearchMethod::Next;
+        assert_eq!(
+            calendar.trading_day_from_datetime(&datetime, method, tf)?,
+            thursday
+        );
+        assert_eq!(
+            calendar.trading_day_from_datetime(&datetime, method, rb)?,
+            friday
+        );
+        // 22:00, 没有夜盘的国债期货取决于method
+        let datetime = date_at_hms(&thursday, 22, 0, 0);
+        let method = NotTradingSearchMethod::Prev;
+        assert_eq!(
+            calendar.trading_day_from_datetime(&datetime, method, tf)?,
+            thursday
+        );
+        assert_eq!(
+            calendar.trading_day_from_datetime(&datetime, method, rb)?,
+            friday
+        );
+
+        assert!(SessionTemplate::new("bad", vec![rb.intervals[2], rb.intervals[1]]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn is_trading_by_session() -> Result<()> {
+        let mut calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let thursday = make_date(2024, 9, 26);
+        // 缺省的连接时段, 8:30就算trading
+        calendar.time_changed(&date_at_hms(&thursday, 8, 45, 0), false)?;
+        assert!(calendar.is_trading());
+
+        let session = SessionRegistry::buildin().template("rb")?.clone();
+        calendar.set_session_template(Some(session));
+        calendar.time_changed(&date_at_hms(&thursday, 8, 45, 0), false)?;
+        assert!(!calendar.is_trading());
+        calendar.time_changed(&date_at_hms(&thursday, 9, 0, 0), false)?;
+        assert!(calendar.is_trading());
+        calendar.time_changed(&date_at_hms(&thursday, 10, 15, 0), false)?;
+        assert!(!calendar.is_trading());
+        calendar.time_changed(&date_at_hms(&thursday, 22, 59, 59), false)?;
+        assert!(calendar.is_trading());
+        calendar.time_changed(&date_at_hms(&thursday, 23, 0, 0), false)?;
+        assert!(!calendar.is_trading());
+        Ok(())
+    }
+
+    #[test]
+    fn session_history() -> Result<()> {
+        let registry = SessionRegistry::buildin();
+        let calendar = get_buildin_calendar(None)?;
+
+        // 2015-12-31 15:10, 股指期货还在交易, 2016年以后15:00收盘
+        let if_old = registry.template_at("IF1601", &make_date(2015, 12, 31))?;
+        let if_new = registry.template_at("IF1601", &make_date(2016, 1, 4))?;
+        assert_eq!(if_old.day_end(), Some(make_time(15, 15, 0)));
+        assert_eq!(if_new.day_end(), Some(make_time(15, 0, 0)));
+        let datetime = date_at_hms(&make_date(2015, 12, 31), 15, 10, 0);
+        assert!(calendar.is_in_session(&datetime, if_old)?);
+        let method = NotTradingSearchMethod::Next;
+        let tday = registry.trading_day_from_datetime(&calendar, "IF", &datetime, method)?;
+        assert_eq!(tday, make_date(2015, 12, 31));
+        let datetime = date_at_hms(&make_date(2016, 1, 4), 15, 10, 0);
+        let tday = registry.trading_day_from_datetime(&calendar, "IF", &datetime, method)?;
+        assert_eq!(tday, make_date(2016, 1, 5));
+
+        // 黄金2013-07-05开始夜盘, 螺纹2016-05-03起夜盘23:00收盘
+        assert!(
+            !registry
+                .template_at("au", &make_date(2013, 7, 4))?
+                .has_night()
+        );
+        assert!(
+            registry
+                .template_at("au", &make_date(2013, 7, 5))?
+                .has_night()
+        );
+        // 铁矿石2014-12-26才开始夜盘, 晚于大商所其他品种的2014-07-04
+        assert!(
+            registry
+                .template_at("m", &make_date(2014, 7, 4))?
+                .has_night()
+        );
+        for date in [make_date(2014, 7, 4), make_date(2014, 12, 25)] {
+            assert!(!registry.template_at("i", &date)?.has_night());
+        }
+        assert!(
+            registry
+                .template_at("i", &make_date(2014, 12, 26))?
+                .has_night()
+        );
+        // 其他品种在夜盘开设(或上市)之前都没有夜盘, 之后与当前的交易时段一致
+        let night_starts = [
+            ("ni", 2015, 3, 27),
+            ("ss", 2019, 9, 25),
+            ("ao", 2023, 6, 19),
+            ("fu", 2018, 7, 16),
+            ("sp", 2018, 11, 27),
+            ("br", 2023, 7, 28),
+            ("sc", 2018, 3, 26),
+            ("bc", 2020, 11, 19),
+            ("nr", 2019, 8, 12),
+            ("lu", 2020, 6, 22),
+            ("l", 2015, 5, 8),
+            ("pp", 2015, 5, 8),
+            ("c", 2019, 3, 29),
+            ("cs", 2019, 3, 29),
+            ("eg", 2018, 12, 10),
+            ("eb", 2019, 9, 26),
+            ("rr", 2019, 8, 16),
+            ("pg", 2020, 3, 30),
+            ("cy", 2017, 8, 18),
+            ("sa", 2019, 12, 6),
+            ("pf", 2020, 10, 12),
+            ("px", 2023, 9, 15),
+            ("sh", 2023, 9, 15),
+        ];
+        for (product, y, m, d) in night_starts {
+            let start = make_date(y, m, d);
+            let before = registry.template_at(product, &yesterday(&start))?;
+            assert!(!before.has_night(), "{} {}", product, start);
+            assert!(registry.template_at(product, &start)?.has_night());
+            assert_eq!(
+                registry.template_at(product, &make_date(2026, 1, 5))?,
+                registry.template(product)?
+            );
+        }
+        let rb = registry.template_at("rb", &make_date(2015, 6, 1))?;
+        let datetime = date_at_hms(&make_date(2015, 6, 2), 0, 30, 0);
+        assert!(calendar.is_in_session(&datetime, rb)?);
+        let rb = registry.template("rb")?;
+        assert!(!calendar.is_in_session(&datetime, rb)?);
+
+        let history = SessionHistory::new(vec![
+            EffectiveSession::new(None, Some(make_date(2020, 1, 1)), SessionTemplate::stock()),
+            EffectiveSession::new(Some(make_date(2019, 1, 1)), None, SessionTemplate::stock()),
+        ]);
+        assert!(history.is_err());
+        Ok(())
+    }
+}
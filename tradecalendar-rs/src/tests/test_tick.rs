@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::get_buildin_calendar;
+    use crate::jcswitch::*;
+
+    #[test]
+    fn bar_of_tick() -> Result<()> {
+        let calendar = get_buildin_calendar(Some(make_date(2024, 1, 1)))?;
+        let thursday = make_date(2024, 9, 26);
+        let friday = make_date(2024, 9, 27);
+        let saturday = make_date(2024, 9, 28);
+        let monday = make_date(2024, 9, 30);
+
+        let bar = |date: &MyDateType, h, m, s, product| {
+            calendar.bar_of_tick(&date_at_hms(date, h, m, s), product)
+        };
+
+        assert_eq!(
+            bar(&friday, 9, 0, 30, "rb2501")?,
+            (friday, date_at_hms(&friday, 9, 0, 0))
+        );
+        // 收盘时间点的tick归入前一根K线
+        for (h, m) in [(10, 15), (11, 30), (15, 0)] {
+            assert_eq!(
+                bar(&friday, h, m, 0, "rb2501")?,
+                (
+                    friday,
+                    datetime_add_secs(&date_at_hms(&friday, h, m, 0), -60)
+                )
+            );
+        }
+        assert_eq!(
+            bar(&thursday, 23, 0, 0, "rb2501")?,
+            (friday, date_at_hms(&thursday, 22, 59, 0))
+        );
+        // 周五夜盘属于下周一, 黄金凌晨盘在周六
+        assert_eq!(
+            bar(&friday, 23, 0, 0, "rb2501")?,
+            (monday, date_at_hms(&friday, 22, 59, 0))
+        );
+        assert_eq!(
+            bar(&saturday, 0, 0, 0, "au2412")?,
+            (monday, date_at_hms(&saturday, 0, 0, 0))
+        );
+        assert_eq!(
+            bar(&saturday, 2, 30, 0, "au2412")?,
+            (monday, date_at_hms(&saturday, 2, 29, 0))
+        );
+
+        // 集合竞价的tick归入第一根K线
+        assert_eq!(
+            bar(&friday, 20, 59, 0, "rb2501")?,
+            (monday, date_at_hms(&friday, 21, 0, 0))
+        );
+        assert_eq!(
+            bar(&friday, 8, 59, 0, "rb2501")?,
+            (friday, date_at_hms(&friday, 9, 0, 0))
+        );
+        assert_eq!(
+            bar(&friday, 9, 29, 0, "IF2410")?,
+            (friday, date_at_hms(&friday, 9, 30, 0))
+        );
+
+        // 非交易时段
+        assert!(bar(&friday, 10, 20, 0, "rb2501").is_err());
+        assert!(bar(&friday, 12, 0, 0, "rb2501").is_err());
+        assert!(bar(&friday, 23, 0, 1, "rb2501").is_err());
+        assert!(bar(&saturday, 1, 0, 0, "rb2501").is_err());
+        // 节假日前没有夜盘
+        assert!(bar(&make_date(2024, 9, 30), 21, 0, 0, "rb2501").is_err());
+        assert!(bar(&friday, 8, 0, 0, "rb2501").is_err());
+        Ok(())
+    }
+}
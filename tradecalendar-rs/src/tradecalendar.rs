@@ -3,22 +3,28 @@ use csv::*;
 use encoding_rs_io::DecodeReaderBytes;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs::File;
 use std::path::Path;
 use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "with-chrono")]
-use chrono::{Datelike, Duration, Timelike, Weekday};
+use chrono::{Datelike, Duration, Weekday};
 
 #[cfg(feature = "with-jiff")]
-use {
-    jiff::civil::{Time, Weekday},
-    jiff::{ToSpan, Unit},
-    std::ops::SubAssign,
-};
+use {jiff::ToSpan, jiff::civil::Weekday, std::ops::SubAssign};
 
+use crate::clock::Clock;
+use crate::exchange::{Exchange, ExchangeTimeZone};
+use crate::holiday::{
+    DayKind, Holiday, HolidayBlock, HolidayTable, add_days, estimate_tradingdays,
+    find_holiday_block, is_weekend, weekday_index,
+};
 use crate::jcswitch::*;
+use crate::overrides::CalendarOverride;
+use crate::session::{SessionPart, SessionRegistry, SessionTemplate, secs_from_midnight};
 
 /// 如果搜索的时间点“不在”交易时段内, 如何返回交易日:
 ///
@@ -35,6 +41,65 @@ pub enum NotTradingSearchMethod {
     Prev,
 }
 
+/// 日历周期, 用于查询周期内的第几个交易日, 如月末最后一个交易日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalendarPeriod {
+    /// 周一至周日
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl CalendarPeriod {
+    /// date所在周期的第一天和最后一天(自然日)
+    pub fn bounds(&self, date: &MyDateType) -> (MyDateType, MyDateType) {
+        let (year, month, _) = date_ymd(date);
+        let months = |first_month: u32, count: u32| {
+            let first = make_date(year, first_month, 1);
+            let next_month = first_month + count;
+            let next = if next_month > 12 {
+                make_date(year + 1, next_month - 12, 1)
+            } else {
+                make_date(year, next_month, 1)
+            };
+            (first, add_days(&next, -1))
+        };
+        match self {
+            CalendarPeriod::Week => {
+                let monday = add_days(date, -weekday_index(date));
+                (monday, add_days(&monday, 6))
+            }
+            CalendarPeriod::Month => months(month, 1),
+            CalendarPeriod::Quarter => months((month - 1) / 3 * 3 + 1, 3),
+            CalendarPeriod::Year => months(1, 12),
+        }
+    }
+}
+
+/// 某个时间点所处的交易阶段, 用于判断是否可以报单
+///
+/// 与check_is_trading()不同, 后者只是判断CTP前置是否可以连接
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TradingPhase {
+    /// 夜盘或日盘开盘前的集合竞价
+    PreOpenAuction,
+    /// 夜盘连续交易, 包含0点之后的凌晨盘
+    ContinuousNight,
+    /// 日盘上午连续交易
+    ContinuousMorning,
+    /// 小节休息, 如10:15~10:30
+    Break,
+    /// 午休
+    LunchBreak,
+    /// 日盘下午连续交易
+    ContinuousAfternoon,
+    /// 交易日内的非交易时段, 如收盘之后
+    Closed,
+    /// 周末及节假日
+    Holiday,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, sqlx::FromRow)]
 pub struct Tradingday {
     pub date: MyDateType,
@@ -72,6 +137,18 @@ impl Tradingday {
         }
     }
 
+    /// 这一自然日的time时刻, 是否处于session的交易区间内
+    pub fn is_in_session(&self, time: &MyTimeType, session: &SessionTemplate) -> bool {
+        match session.interval_at(time) {
+            Some(interval) => match interval.part {
+                SessionPart::Night => self.night,
+                SessionPart::Morning => self.morning,
+                SessionPart::Day => self.trading,
+            },
+            None => false,
+        }
+    }
+
     /// 文件头：date,morning,trading,night,next
     ///
     /// 行格式：2009-01-03,false,false,false,2009-01-04
@@ -189,6 +266,47 @@ fn is_working_day(the_day: &MyDateType) -> bool {
     return week_day != Weekday::Saturday && week_day != Weekday::Sunday && !isfirst;
 }
 
+/// 集合竞价的tick最多比开盘时间提前的秒数
+const AUCTION_SECS: i64 = 5 * 60;
+
+/// 交易日的交易区间(开始, 结束)
+type SessionIntervals = Vec<(MyDateTimeType, MyDateTimeType)>;
+
+/// 自然日input.date()的时间点, 只可能属于其后(含当天)的前两个交易日, 依次返回(交易日, 交易时段, 交易区间)
+///
+/// session_of: 交易日使用的交易时段
+fn candidate_sessions<'a, C, F>(
+    cache: &'a C,
+    input: &MyDateTimeType,
+    session_of: F,
+) -> Result<impl Iterator<Item = Result<(MyDateType, &'a SessionTemplate, SessionIntervals)>> + 'a>
+where
+    C: TradingdayCache + ?Sized,
+    F: Fn(&MyDateType) -> Result<&'a SessionTemplate> + 'a,
+{
+    let first = cache
+        .get_next_trading_day(&yesterday(&input.date()), 1)?
+        .date;
+    let mut candidates = vec![first];
+    if let Ok(second) = cache.get_next_trading_day(&first, 1) {
+        candidates.push(second.date);
+    }
+    Ok(candidates.into_iter().map(move |tday| {
+        let session = session_of(&tday)?;
+        let intervals = cache.session_intervals_with(&tday, session)?;
+        Ok((tday, session, intervals))
+    }))
+}
+
+/// intervals[idx]是否为夜盘或日盘的第一个交易区间, day_start为日盘开盘时间
+fn is_segment_first(
+    intervals: &[(MyDateTimeType, MyDateTimeType)],
+    idx: usize,
+    day_start: Option<MyDateTimeType>,
+) -> bool {
+    idx == 0 || day_start.is_some_and(|d| intervals[idx].0 == d)
+}
+
 /// 内部是无状态的
 pub trait TradingdayCache {
     /// 获取原始的日期列表(含非交易日)
@@ -309,6 +427,183 @@ pub trait TradingdayCache {
         ));
     }
 
+    /// 交易日date在get_trading_day_list()中的序号, date不是交易日时返回None
+    fn ordinal(&self, date: &MyDateType) -> Option<usize> {
+        let (_, mid, _) = search_days(self.get_trading_day_list(), date);
+        (mid >= 0).then_some(mid as usize)
+    }
+
+    /// 序号为ordinal的交易日, 参见ordinal()
+    fn tradingday_at_ordinal(&self, ordinal: usize) -> Option<&Tradingday> {
+        self.get_trading_day_list().get(ordinal)
+    }
+
+    /// date之后(n>0)或之前(n<0)的第|n|个交易日, 如T+3, T-5, 超出范围返回错误
+    ///
+    /// n为0时返回date本身, 要求date是交易日; n不为0时与get_next_trading_day()/get_prev_trading_day()一致
+    fn offset(&self, date: &MyDateType, n: i64) -> Result<&Tradingday> {
+        let list = self.get_trading_day_list();
+        let (left, mid, _) = search_days(list, date);
+        let index = if mid >= 0 {
+            (mid as i64).checked_add(n)
+        } else if n > 0 {
+            (left as i64).checked_add(n)
+        } else if n < 0 {
+            (left as i64 + 1).checked_add(n)
+        } else {
+            return Err(anyhow!("{} is not a trading day", date));
+        };
+        if let Some(index) = index
+            && index >= 0
+            && let Some(tday) = list.get(index as usize)
+        {
+            return Ok(tday);
+        }
+        Err(anyhow!(
+            "offset({}, {}) out of range. {:?} ~ {:?}",
+            date,
+            n,
+            self.min_date(),
+            self.max_date()
+        ))
+    }
+
+    /// 从a(不含)到b(含)之间交易日的个数, b早于a时为负数, a、b都是交易日时offset(a, n)即为b
+    ///
+    /// a、b可以是非交易日, 超出范围返回错误
+    fn signed_distance(&self, a: &MyDateType, b: &MyDateType) -> Result<i64> {
+        let (Some(min_date), Some(max_date)) = (self.min_date(), self.max_date()) else {
+            return Err(anyhow!("empty calendar"));
+        };
+        for date in [a, b] {
+            if date < min_date || date > max_date {
+                return Err(anyhow!(
+                    "out of range. {:?} ~ {:?}",
+                    self.min_date(),
+                    self.max_date()
+                ));
+            }
+        }
+        // 小于等于date的交易日个数
+        let rank = |date: &MyDateType| {
+            let (left, mid, _) = search_days(self.get_trading_day_list(), date);
+            if mid >= 0 {
+                mid as i64 + 1
+            } else {
+                left as i64 + 1
+            }
+        };
+        Ok(rank(b) - rank(a))
+    }
+
+    /// date所在周期内的全部交易日, 周期超出日历范围时返回错误
+    fn trading_days_in_period(
+        &self,
+        date: &MyDateType,
+        period: CalendarPeriod,
+    ) -> Result<&[Tradingday]> {
+        let (first, last) = period.bounds(date);
+        match (self.min_date(), self.max_date()) {
+            (Some(min_date), Some(max_date)) if min_date <= &first && &last <= max_date => {
+                Ok(self.get_trading_day_slice(&first, &last))
+            }
+            _ => Err(anyhow!(
+                "{:?} of {} ({} ~ {}) out of range. {:?} ~ {:?}",
+                period,
+                date,
+                first,
+                last,
+                self.min_date(),
+                self.max_date()
+            )),
+        }
+    }
+
+    /// date所在周期内的第n个交易日, n为负数时从后往前数, -1为最后一个交易日
+    ///
+    /// n为0、周期内交易日不足|n|个或周期超出日历范围时返回错误
+    fn nth_in_period(
+        &self,
+        date: &MyDateType,
+        period: CalendarPeriod,
+        n: i64,
+    ) -> Result<&Tradingday> {
+        let list = self.trading_days_in_period(date, period)?;
+        let index = match n {
+            1.. => Some(n - 1),
+            ..0 => (list.len() as i64).checked_add(n),
+            0 => None,
+        };
+        index
+            .filter(|index| *index >= 0)
+            .and_then(|index| list.get(index as usize))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{:?} of {} has {} trading days, n = {}",
+                    period,
+                    date,
+                    list.len(),
+                    n
+                )
+            })
+    }
+
+    /// date所在周期内的第几个交易日(从1开始), 即周期开始至date(含)的交易日个数
+    ///
+    /// date不是交易日时为其前一交易日的序号, 周期内date之前没有交易日时为0
+    fn index_in_period(&self, date: &MyDateType, period: CalendarPeriod) -> Result<usize> {
+        let list = self.trading_days_in_period(date, period)?;
+        Ok(list.iter().take_while(|tday| &tday.date <= date).count())
+    }
+
+    fn first_in_week(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Week, 1)
+    }
+
+    fn last_in_week(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Week, -1)
+    }
+
+    fn nth_in_week(&self, date: &MyDateType, n: i64) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Week, n)
+    }
+
+    fn first_in_month(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Month, 1)
+    }
+
+    fn last_in_month(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Month, -1)
+    }
+
+    fn nth_in_month(&self, date: &MyDateType, n: i64) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Month, n)
+    }
+
+    fn first_in_quarter(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Quarter, 1)
+    }
+
+    fn last_in_quarter(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Quarter, -1)
+    }
+
+    fn nth_in_quarter(&self, date: &MyDateType, n: i64) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Quarter, n)
+    }
+
+    fn first_in_year(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Year, 1)
+    }
+
+    fn last_in_year(&self, date: &MyDateType) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Year, -1)
+    }
+
+    fn nth_in_year(&self, date: &MyDateType, n: i64) -> Result<&Tradingday> {
+        self.nth_in_period(date, CalendarPeriod::Year, n)
+    }
+
     /// 计算从start_date(含)到end_date(含)之间交易日的个数, 超出范围的部分将被忽略
     fn get_trading_days_count(&self, start_dt: &MyDateType, end_dt: &MyDateType) -> usize {
         if start_dt > end_dt {
@@ -339,18 +634,73 @@ pub trait TradingdayCache {
         }
     }
 
+    /// 交易日date之后休市的自然日天数, 即date与next之间的天数, 普通周末为2, date不是交易日时返回错误
+    fn closed_days_after(&self, date: &MyDateType) -> Result<i32> {
+        match self.get_date_detail(date) {
+            Some(tday) if tday.trading => {
+                Ok(date_to_days_since_epoch(&tday.next) - date_to_days_since_epoch(date) - 1)
+            }
+            Some(_) => Err(anyhow!("{} is not a trading day", date)),
+            None => Err(anyhow!(
+                "out of range. {:?} ~ {:?}",
+                self.min_date(),
+                self.max_date()
+            )),
+        }
+    }
+
+    /// date是否为休市至少min_days个自然日之前的最后一个交易日, min_days为3则不含普通周末
+    fn is_pre_holiday(&self, date: &MyDateType, min_days: i32) -> Result<bool> {
+        if !self.is_trading_day(date)? {
+            return Ok(false);
+        }
+        Ok(self.closed_days_after(date)? >= min_days)
+    }
+
+    /// date是否为休市至少min_days个自然日之后的第一个交易日, min_days为3则不含普通周末
+    fn is_post_holiday(&self, date: &MyDateType, min_days: i32) -> Result<bool> {
+        if !self.is_trading_day(date)? {
+            return Ok(false);
+        }
+        let prev = self.get_prev_trading_day(date, 1)?;
+        Ok(self.closed_days_after(&prev.date)? >= min_days)
+    }
+
+    /// date(含)之后第一个满足is_pre_holiday()的交易日, 超出范围返回None
+    fn next_pre_holiday(&self, date: &MyDateType, min_days: i32) -> Option<&Tradingday> {
+        let list = self.get_trading_day_list();
+        let (_, mid, right) = search_days(list, date);
+        let start = if mid >= 0 { mid } else { right };
+        if start < 0 {
+            return None;
+        }
+        list[start as usize..].iter().find(|tday| {
+            self.closed_days_after(&tday.date)
+                .is_ok_and(|days| days >= min_days)
+        })
+    }
+
+    /// 从date(含)起, 到下一次休市至少min_days个自然日之前的最后一个交易日, 还有几个交易日
+    ///
+    /// date就是该交易日时为0, date不是交易日时从其后第一个交易日算起, 超出范围返回None
+    fn trading_days_until_closure(&self, date: &MyDateType, min_days: i32) -> Option<usize> {
+        let pre_holiday = self.next_pre_holiday(date, min_days)?;
+        Some(self.get_trading_days_count(date, &pre_holiday.date) - 1)
+    }
+
     /// 根据输入时间获取交易日,
     ///
     /// 如果输入的时间点是非交易时段, 则利用method确定是取前一个交易日, 还是后一交易日,
     ///
     /// 交易时段内的时间点不受影响
     ///
-    /// is_finance_item, 金融期货的下午收盘时间点为15:15, 其他商品15:00
+    /// session: 品种的交易时段, 用来确定日盘开盘、收盘及夜盘开始的时间点,
+    /// 比如金融期货的下午收盘时间点为15:15, 其他商品15:00; 没有夜盘的品种, 收盘后都是非交易时段
     fn trading_day_from_datetime(
         &self,
         input: &MyDateTimeType,
         method: NotTradingSearchMethod,
-        is_finance_item: bool,
+        session: &SessionTemplate,
     ) -> Result<MyDateType> {
         let list = self.get_full_day_list();
         let date = input.date();
@@ -366,19 +716,24 @@ pub trait TradingdayCache {
         let index = index as usize;
         let tday = &list[index];
         assert!(tday.date == date);
-        // 如果是金融期货，下午收盘时间15：15，否则收盘时间15：00
-        let day_after = if is_finance_item {
-            15 * 3600 + 15 * 60
-        } else {
-            15 * 3600
-        };
+        // 日盘开盘、收盘时间, 缺省为9:00, 15:00
+        let day_begin = session
+            .day_begin()
+            .map(|t| secs_from_midnight(&t))
+            .unwrap_or(9 * 3600);
+        let day_after = session
+            .day_end()
+            .map(|t| secs_from_midnight(&t))
+            .unwrap_or(15 * 3600);
+        // 没有夜盘的品种, 收盘之后到24:00都是非交易时段
+        let night_begin = session
+            .night_begin()
+            .map(|t| secs_from_midnight(&t))
+            .unwrap_or(24 * 3600);
 
         let time = input.time();
-        #[cfg(feature = "with-chrono")]
-        let secs = time.num_seconds_from_midnight();
-        #[cfg(feature = "with-jiff")]
-        let secs = time.since(Time::midnight()).unwrap().total(Unit::Second)? as i32;
-        if secs < 3600 * 9 {
+        let secs = secs_from_midnight(&time);
+        if secs < day_begin {
             // [0:00, 09:00)
             if tday.morning {
                 if tday.trading {
@@ -404,7 +759,7 @@ pub trait TradingdayCache {
             } else {
                 return Ok(self.__by_prev_tday(index, tday, method));
             }
-        } else if secs < 21 * 3600 {
+        } else if secs < night_begin {
             // (15:00/15:15, 21:00)
             if tday.trading {
                 return Ok(match method {
@@ -436,6 +791,251 @@ pub trait TradingdayCache {
         }
     }
 
+    /// 根据tick的时间获取(交易日, 所属1分钟K线的开始时间)
+    ///
+    /// 恰好在收盘时间点(如10:15:00, 11:30:00, 15:00:00, 23:00:00)的tick归入前一根K线,
+    /// 夜盘/日盘开盘前的集合竞价tick(如20:59, 08:59)归入第一根K线, 非交易时段的tick报错
+    fn bar_of_tick_with(
+        &self,
+        input: &MyDateTimeType,
+        session: &SessionTemplate,
+    ) -> Result<(MyDateType, MyDateTimeType)> {
+        for candidate in candidate_sessions(self, input, |_| Ok(session))? {
+            let (tday, session, intervals) = candidate?;
+            let day_start = session.day_begin().map(|t| date_at_time(&tday, &t));
+            for (idx, (begin, end)) in intervals.iter().enumerate() {
+                if input < begin {
+                    // 夜盘或者日盘的第一个交易区间, 接受集合竞价的tick
+                    if is_segment_first(&intervals, idx, day_start)
+                        && datetime_diff_secs(input, begin) < AUCTION_SECS
+                    {
+                        return Ok((tday, *begin));
+                    }
+                    continue;
+                }
+                let secs = datetime_diff_secs(begin, input);
+                let duration = datetime_diff_secs(begin, end);
+                if secs < duration {
+                    return Ok((tday, datetime_add_secs(begin, secs / 60 * 60)));
+                } else if secs == duration && intervals.get(idx + 1).is_none_or(|n| n.0 != *end) {
+                    // 收盘时间点的tick, 0:00跨日的区间不算收盘
+                    return Ok((tday, datetime_add_secs(end, -60)));
+                }
+            }
+        }
+        Err(anyhow!("off-session tick at {}", input))
+    }
+
+    /// 与trading_day_from_datetime()相同, 输入为UTC时间戳(纳秒), 先转换为tz时区的本地时间
+    fn trading_day_from_utc_nanos(
+        &self,
+        nanos: i64,
+        tz: ExchangeTimeZone,
+        method: NotTradingSearchMethod,
+        session: &SessionTemplate,
+    ) -> Result<MyDateType> {
+        self.trading_day_from_datetime(&tz.from_utc_nanos(nanos), method, session)
+    }
+
+    /// 与trading_day_from_datetime()相同, 输入为带时区的时间, 先转换为tz时区的本地时间
+    fn trading_day_from_zoned(
+        &self,
+        input: &MyZonedType,
+        tz: ExchangeTimeZone,
+        method: NotTradingSearchMethod,
+        session: &SessionTemplate,
+    ) -> Result<MyDateType> {
+        self.trading_day_from_datetime(&tz.from_zoned(input), method, session)
+    }
+
+    /// 根据CTP行情的TradingDay和UpdateTime, 还原tick真实的自然日时间
+    ///
+    /// CTP的ActionDay各交易所不一致: 大商所夜盘的ActionDay填的是TradingDay, 周五夜盘0点之后的tick
+    /// 则在周六; 郑商所夜盘的TradingDay填的是自然日, 所以郑商所直接使用trading_day作为日期.
+    /// 其他交易所的夜盘(晚上18:00之后)属于前一交易日晚上, 凌晨盘(早上6:00之前)属于前一交易日的下一自然日
+    fn action_datetime(
+        &self,
+        trading_day: &MyDateType,
+        update_time: &MyTimeType,
+        exchange: Exchange,
+    ) -> Result<MyDateTimeType> {
+        if exchange == Exchange::CZCE || exchange.night_start().is_none() {
+            return Ok(date_at_time(trading_day, update_time));
+        }
+        let tday = self.get_date_detail(trading_day).ok_or_else(|| {
+            anyhow!(
+                "out of range. {:?} ~ {:?}",
+                self.min_date(),
+                self.max_date()
+            )
+        })?;
+        if !tday.trading {
+            return Err(anyhow!("{} is not a trading day", trading_day));
+        }
+        let secs = secs_from_midnight(update_time);
+        if (6 * 3600..18 * 3600).contains(&secs) {
+            return Ok(date_at_time(trading_day, update_time));
+        }
+        let prev = self.get_prev_trading_day(trading_day, 1)?;
+        if !prev.night {
+            return Err(anyhow!(
+                "no night session before {}, update time {}",
+                trading_day,
+                update_time
+            ));
+        }
+        if secs >= 18 * 3600 {
+            Ok(date_at_time(&prev.date, update_time))
+        } else {
+            // 凌晨盘在夜盘的下一自然日, 周五夜盘则在周六
+            let morning_date = tomorrow(&prev.date);
+            match self.get_date_detail(&morning_date) {
+                Some(day) if !day.morning => Err(anyhow!(
+                    "no morning session on {}, update time {}",
+                    morning_date,
+                    update_time
+                )),
+                _ => Ok(date_at_time(&morning_date, update_time)),
+            }
+        }
+    }
+
+    /// 输入的时间点是否处于session的交易区间内, 超出日历范围时报错
+    fn is_in_session(&self, input: &MyDateTimeType, session: &SessionTemplate) -> Result<bool> {
+        match self.get_date_detail(&input.date()) {
+            Some(tday) => Ok(tday.is_in_session(&input.time(), session)),
+            None => Err(anyhow!(
+                "out of range. {:?} ~ {:?}",
+                self.min_date(),
+                self.max_date()
+            )),
+        }
+    }
+
+    /// 交易日tday在session下的所有交易区间(开始, 结束), 按时间排序:
+    ///
+    /// 前一交易日夜里的夜盘, 跨越0点之后的凌晨盘(周五夜盘则在周六凌晨), 以及当天的日盘;
+    /// 前一交易日没有夜盘(如节假日前)时, 只有日盘
+    fn session_intervals_with(
+        &self,
+        tday: &MyDateType,
+        session: &SessionTemplate,
+    ) -> Result<Vec<(MyDateTimeType, MyDateTimeType)>> {
+        let list = self.get_full_day_list();
+        let (_, index, _) = search_days(list, tday);
+        if index < 0 {
+            return Err(anyhow!(
+                "out of range. {:?} ~ {:?}",
+                self.min_date(),
+                self.max_date()
+            ));
+        }
+        let index = index as usize;
+        let the_day = &list[index];
+        if !the_day.trading {
+            return Err(anyhow!("{} is not a trading day", tday));
+        }
+
+        let mut result = Vec::with_capacity(session.intervals.len());
+        if session.has_night() {
+            let prev_tday = self.__fast_prev_trading_day(index).ok_or_else(|| {
+                anyhow!(
+                    "prev trading day of {} out of range. {:?} ~ {:?}",
+                    tday,
+                    self.min_date(),
+                    self.max_date()
+                )
+            })?;
+            if prev_tday.night {
+                let night_date = prev_tday.date;
+                let morning_date = tomorrow(&night_date);
+                // 凌晨盘所在自然日的morning, 一般与前一交易日的night一致
+                let has_morning = self
+                    .get_date_detail(&morning_date)
+                    .is_none_or(|d| d.morning);
+                for interval in session.intervals.iter() {
+                    match interval.part {
+                        SessionPart::Night => {
+                            let begin = date_at_time(&night_date, &interval.begin);
+                            let end = if interval.end_secs() >= 24 * 3600 {
+                                date_at_time(&morning_date, &interval.end)
+                            } else {
+                                date_at_time(&night_date, &interval.end)
+                            };
+                            result.push((begin, end));
+                        }
+                        SessionPart::Morning if has_morning => {
+                            result.push((
+                                date_at_time(&morning_date, &interval.begin),
+                                date_at_time(&morning_date, &interval.end),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        for interval in session
+            .intervals
+            .iter()
+            .filter(|i| i.part == SessionPart::Day)
+        {
+            result.push((
+                date_at_time(tday, &interval.begin),
+                date_at_time(tday, &interval.end),
+            ));
+        }
+        Ok(result)
+    }
+
+    /// 交易日tday在session下所有K线的(开始, 结束)时间, 按时间排序, minutes为K线周期(分钟)
+    ///
+    /// 按交易时间累计分钟数, 所以K线可以跨越小节休息和午休(如60分钟线10:00~11:15, 11:15~14:15),
+    /// 但不跨越夜盘和日盘, 夜盘/日盘收盘时不足周期的部分单独作为一根K线
+    fn bar_timestamps_with(
+        &self,
+        tday: &MyDateType,
+        session: &SessionTemplate,
+        minutes: u32,
+    ) -> Result<Vec<(MyDateTimeType, MyDateTimeType)>> {
+        if minutes == 0 {
+            return Err(anyhow!("bar period must be greater than 0"));
+        }
+        let intervals = self.session_intervals_with(tday, session)?;
+        let day_start = match session.day_begin() {
+            Some(t) => date_at_time(tday, &t),
+            None => date_at_time(tday, &make_time(0, 0, 0)),
+        };
+        let mut result = Vec::with_capacity(600 / minutes as usize + 4);
+        // 当前K线的开始时间, 已经累计的分钟数
+        let mut bar_open: Option<MyDateTimeType> = None;
+        let mut count = 0_u32;
+        for (idx, (begin, end)) in intervals.iter().enumerate() {
+            let mut minute_begin = *begin;
+            while minute_begin < *end {
+                let minute_end = datetime_add_secs(&minute_begin, 60);
+                let open = *bar_open.get_or_insert(minute_begin);
+                count += 1;
+                if count == minutes {
+                    result.push((open, minute_end));
+                    bar_open = None;
+                    count = 0;
+                }
+                minute_begin = minute_end;
+            }
+            // 夜盘(含凌晨盘)结束, 或者日盘结束
+            let segment_end = match intervals.get(idx + 1) {
+                Some((next_begin, _)) => *end <= day_start && *next_begin >= day_start,
+                None => true,
+            };
+            if segment_end && let Some(open) = bar_open.take() {
+                result.push((open, *end));
+                count = 0;
+            }
+        }
+        Ok(result)
+    }
+
     /// 在full_day_list里面，快速找到上一个交易日, 调用get_prev_trading_day()开销太大
     ///
     /// full_list_idx开始的下标(不含),向前搜索
@@ -479,6 +1079,55 @@ pub trait TradingdayCache {
     }
 }
 
+/// 从完整日期列表中筛选出交易日
+pub(crate) fn filter_trading_days(full_list: &[Tradingday]) -> Vec<Tradingday> {
+    full_list.iter().filter(|td| td.trading).cloned().collect()
+}
+
+/// 无状态的交易日历, 仅持有日期列表, 用于各交易所/市场的独立日历
+#[derive(Debug, Clone, Default)]
+pub struct StaticCalendar {
+    full_day_list: Vec<Tradingday>,
+    trading_day_list: Vec<Tradingday>,
+    /// 已应用的临时调整, 按日期排序
+    overrides: Vec<CalendarOverride>,
+}
+
+impl TradingdayCache for StaticCalendar {
+    fn get_full_day_list(&self) -> &Vec<Tradingday> {
+        &self.full_day_list
+    }
+
+    fn get_trading_day_list(&self) -> &Vec<Tradingday> {
+        &self.trading_day_list
+    }
+}
+
+impl StaticCalendar {
+    /// full_list必须按日期排序, 且不能为空
+    pub fn new(full_list: Vec<Tradingday>) -> Result<Self> {
+        if full_list.is_empty() {
+            return Err(anyhow!("StaticCalendar: full_list is empty."));
+        }
+        Ok(Self {
+            trading_day_list: filter_trading_days(&full_list),
+            full_day_list: full_list,
+            overrides: Vec::new(),
+        })
+    }
+
+    /// 记录已应用到full_list上的临时调整, 参见CalendarOverrides::apply()
+    pub fn with_overrides(mut self, overrides: Vec<CalendarOverride>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// 已应用的临时调整(日期、类型、原因), 用于审计
+    pub fn overrides(&self) -> &[CalendarOverride] {
+        &self.overrides
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -526,6 +1175,59 @@ impl Display for TradingCheckConfig {
     }
 }
 
+/// 状态切换的类型, 参见TradeCalendar::next_transition()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionKind {
+    /// is_trading变为true
+    TradingStarted,
+    /// is_trading变为false
+    TradingStopped,
+    /// 交易日改变, 在tday_shift或者0点
+    TradingDayChanged,
+    /// 自然日改变
+    NaturalDayChanged,
+}
+
+/// 下一个状态切换的时刻, 同一时刻可能有多种切换
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub at: MyDateTimeType,
+    pub kinds: Vec<TransitionKind>,
+}
+
+/// time_changed()产生的状态改变事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalendarEvent {
+    /// 自然日改变
+    NaturalDayChanged { prev: MyDateType, curr: MyDateType },
+    /// 交易日改变, prev/next为新交易日的前后交易日
+    TradingDayChanged {
+        prev: MyDateType,
+        curr: MyDateType,
+        next: MyDateType,
+    },
+    /// is_trading变为true
+    TradingStarted,
+    /// is_trading变为false
+    TradingStopped,
+    /// 日历数据缺失, 按工作日推算
+    FailSafeUsed { reason: String },
+    /// 日历数据即将用完, days_left为当前自然日之后剩余的天数
+    CoverageEnding { days_left: usize },
+}
+
+/// 事件监听者
+pub type CalendarListener = Box<dyn FnMut(&CalendarEvent) + Send + Sync>;
+
+/// time_changed()的返回值
+type TimeChange = (
+    MyDateType,
+    MyDateType,
+    MyDateType,
+    MyDateType,
+    Option<String>,
+);
+
 /// 内部是有状态的，维护着当前自然日，交易日等信息
 /// 如果交易日当天有夜盘，则self.cfg._night_begin作为下一个TradingDay的开始
 /// 如果交易日当天没有夜盘，则夜里23:59:59之后的0点作为下一个TradingDay的开始
@@ -543,6 +1245,20 @@ pub struct TradeCalendar {
     /// 当前时间点, 交易接口是否可连接（CTP服务器开放时段）
     is_trading: bool,
     cfg: TradingCheckConfig,
+    /// 如果设置了交易时段, 则is_trading按照交易时段精确判断, 不再使用cfg的连接时段
+    session: Option<SessionTemplate>,
+    /// 品种 => 交易时段
+    sessions: SessionRegistry,
+    /// 事件监听者
+    listeners: Vec<CalendarListener>,
+    /// 日历数据剩余天数不大于此值时, 产生CoverageEnding事件
+    coverage_warn_days: usize,
+    /// None表示系统时钟
+    clock: Option<Arc<dyn Clock>>,
+    /// 节假日及调休上班日, 用于day_kind()
+    holiday_table: Option<HolidayTable>,
+    /// 按年缓存的estimate_tradingdays()结果, None表示该年无法估算
+    estimated_years: Mutex<HashMap<i32, Option<Arc<Vec<Tradingday>>>>>,
 
     curr_tday: MyDateType,
     next_tday: MyDateType,
@@ -572,6 +1288,8 @@ impl TradeCalendar {
             next_tday: llago,
             prev_tday: llago,
             current_time: date_at_hms(&llago, 0, 0, 0),
+            sessions: SessionRegistry::buildin(),
+            coverage_warn_days: 30,
             ..Default::default()
         }
     }
@@ -642,17 +1360,117 @@ impl TradeCalendar {
         &self.cfg
     }
 
+    /// 设置交易时段, 之后is_trading()表示当前时间点是否处于交易时段内(而不是CTP可连接时段),
+    /// 设置为None则恢复使用TradingCheckConfig;
+    /// 调用此函数之后，可以调用time_changed()刷新状态
+    pub fn set_session_template(&mut self, session: Option<SessionTemplate>) {
+        self.session = session;
+    }
+
+    pub fn get_session_template(&self) -> Option<&SessionTemplate> {
+        self.session.as_ref()
+    }
+
+    /// 替换品种交易时段的查询表, 缺省为SessionRegistry::buildin()
+    pub fn set_session_registry(&mut self, sessions: SessionRegistry) {
+        self.sessions = sessions;
+    }
+
+    pub fn get_session_registry(&self) -> &SessionRegistry {
+        &self.sessions
+    }
+
+    /// 根据tick的时间获取品种product的(交易日, 所属1分钟K线的开始时间), 使用当时有效的交易时段,
+    /// 参见TradingdayCache::bar_of_tick_with()
+    pub fn bar_of_tick(
+        &self,
+        input: &MyDateTimeType,
+        product: &str,
+    ) -> Result<(MyDateType, MyDateTimeType)> {
+        self.sessions.bar_of_tick(self, product, input)
+    }
+
+    /// 品种product在input时间点所处的交易阶段, 使用当时有效的交易时段
+    ///
+    /// 集合竞价只计交易日的第一个交易区间之前: 有夜盘的品种为夜盘开盘前, 日盘开盘前没有集合竞价
+    /// (节后第一天没有夜盘时为日盘开盘前);
+    /// 股票9:15~9:25的集合竞价没有单独区分, 只有开盘前5分钟为PreOpenAuction.
+    /// 不在任何交易时段内时, input所在自然日为交易日则为Closed, 否则为Holiday
+    pub fn phase_at(&self, input: &MyDateTimeType, product: &str) -> Result<TradingPhase> {
+        // 中午12:00, 用来区分上午和下午
+        const NOON: i64 = 12 * 3600;
+        let date = input.date();
+        let session_of = |tday: &MyDateType| self.sessions.template_at(product, tday);
+        for candidate in candidate_sessions(self, input, session_of)? {
+            let (tday, session, intervals) = candidate?;
+            let day_start = session.day_begin().map(|t| date_at_time(&tday, &t));
+            let is_night = |begin: &MyDateTimeType| day_start.is_some_and(|d| *begin < d);
+            for (idx, (begin, end)) in intervals.iter().enumerate() {
+                if begin <= input && input < end {
+                    return Ok(if is_night(begin) {
+                        TradingPhase::ContinuousNight
+                    } else if secs_from_midnight(&begin.time()) < NOON {
+                        TradingPhase::ContinuousMorning
+                    } else {
+                        TradingPhase::ContinuousAfternoon
+                    });
+                }
+                if input >= begin {
+                    continue;
+                }
+                if is_segment_first(&intervals, idx, day_start) {
+                    if idx == 0 && datetime_diff_secs(input, begin) < AUCTION_SECS {
+                        return Ok(TradingPhase::PreOpenAuction);
+                    }
+                } else if intervals[idx - 1].1 <= *input {
+                    let prev_end = secs_from_midnight(&intervals[idx - 1].1.time());
+                    let lunch = !is_night(begin)
+                        && prev_end <= NOON
+                        && secs_from_midnight(&begin.time()) >= NOON;
+                    return Ok(if lunch {
+                        TradingPhase::LunchBreak
+                    } else {
+                        TradingPhase::Break
+                    });
+                }
+            }
+        }
+        match self.get_date_detail(&date) {
+            Some(day) if day.trading => Ok(TradingPhase::Closed),
+            _ => Ok(TradingPhase::Holiday),
+        }
+    }
+
+    /// 品种product在交易日tday所有K线的(开始, 结束)时间, minutes为K线周期(分钟),
+    /// 参见TradingdayCache::bar_timestamps_with()
+    pub fn bar_timestamps(
+        &self,
+        tday: &MyDateType,
+        product: &str,
+        minutes: u32,
+    ) -> Result<Vec<(MyDateTimeType, MyDateTimeType)>> {
+        let session = self.sessions.template_at(product, tday)?;
+        self.bar_timestamps_with(tday, session, minutes)
+    }
+
+    /// 品种product在交易日tday的所有交易区间(开始, 结束), 使用当时有效的交易时段,
+    /// 参见TradingdayCache::session_intervals_with()
+    pub fn session_intervals(
+        &self,
+        tday: &MyDateType,
+        product: &str,
+    ) -> Result<Vec<(MyDateTimeType, MyDateTimeType)>> {
+        let session = self.sessions.template_at(product, tday)?;
+        self.session_intervals_with(tday, session)
+    }
+
     /// 重新加载交易日历列表，年末时交易日历需更新，使用此函数日常重新加载
     /// 调用此函数之后，可以调用time_changed()刷新状态
     pub fn reload(&mut self, full_list: Vec<Tradingday>) -> Result<()> {
         if full_list.is_empty() {
             return Err(anyhow!("TradeCalendar: full_list is empty."));
         }
-        self.trading_day_list = full_list
-            .iter()
-            .filter(|td| td.trading)
-            .map(|x| x.clone())
-            .collect();
+        self.trading_day_list = filter_trading_days(&full_list);
         self.full_day_list = full_list;
         Ok(())
     }
@@ -682,12 +1500,21 @@ impl TradeCalendar {
         Ok(())
     }
 
+    /// 与reset()相同, 但是不通知已注册的listener, reset之后listener保持不变
+    pub(crate) fn reset_silently(&mut self, start_time: Option<&MyDateTimeType>) -> Result<()> {
+        let listeners = std::mem::take(&mut self.listeners);
+        let result = self.reset(start_time);
+        self.listeners = listeners;
+        result
+    }
+
     /// 时间改变，重新计算内部状态
     ///
     /// fail_safe: 在失败时(主要是calendar没有及时更新的情况)尝试补救?
     ///
     /// 返回值: tuple(上个交易日, 当前交易日, 上个自然日, 当前自然日, Option<Error_Message>)
     ///    
+    /// 注册的listener在此时被调用, 参见time_changed_events()
     pub fn time_changed(
         &mut self,
         datetime: &MyDateTimeType,
@@ -699,13 +1526,279 @@ impl TradeCalendar {
         MyDateType,
         Option<String>,
     )> {
+        let (change, _) = self.apply_time(datetime, fail_safe)?;
+        Ok(change)
+    }
+
+    /// 与time_changed()相同, 但是以事件列表的形式返回状态改变, 无需调用方比较前后状态
+    ///
+    /// 注册的listener会按顺序收到同样的事件
+    pub fn time_changed_events(
+        &mut self,
+        datetime: &MyDateTimeType,
+        fail_safe: bool,
+    ) -> Result<Vec<CalendarEvent>> {
+        let (_, events) = self.apply_time(datetime, fail_safe)?;
+        Ok(events)
+    }
+
+    /// 与time_changed_events()相同, 输入为UTC时间戳(纳秒), 先转换为tz时区的本地时间
+    pub fn time_changed_utc_nanos(
+        &mut self,
+        nanos: i64,
+        tz: ExchangeTimeZone,
+        fail_safe: bool,
+    ) -> Result<Vec<CalendarEvent>> {
+        self.time_changed_events(&tz.from_utc_nanos(nanos), fail_safe)
+    }
+
+    /// 与time_changed_events()相同, 输入为带时区的时间, 先转换为tz时区的本地时间
+    pub fn time_changed_zoned(
+        &mut self,
+        input: &MyZonedType,
+        tz: ExchangeTimeZone,
+        fail_safe: bool,
+    ) -> Result<Vec<CalendarEvent>> {
+        self.time_changed_events(&tz.from_zoned(input), fail_safe)
+    }
+
+    /// 注册事件监听者, time_changed()及time_changed_events()产生的每个事件都会通知所有listener
+    pub fn add_listener<F>(&mut self, listener: F)
+    where
+        F: FnMut(&CalendarEvent) + Send + Sync + 'static,
+    {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// 移除所有listener
+    pub fn clear_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
+    /// 设置时钟, 用于测试或模拟, 缺省为系统时钟
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// 按时钟获取当前时间
+    pub fn now(&self) -> MyDateTimeType {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => get_now(),
+        }
+    }
+
+    /// 按时钟的当前时间调用time_changed_events()
+    pub fn refresh(&mut self, fail_safe: bool) -> Result<Vec<CalendarEvent>> {
+        let now = self.now();
+        self.time_changed_events(&now, fail_safe)
+    }
+
+    /// 时钟经过nanos纳秒需要等待的真实时间, 参见Clock::real_duration()
+    pub fn real_duration(&self, nanos: i64) -> std::time::Duration {
+        match &self.clock {
+            Some(clock) => clock.real_duration(nanos),
+            None => std::time::Duration::from_nanos(nanos.max(0) as u64),
+        }
+    }
+
+    /// 日历数据剩余天数不大于days时, 自然日改变时产生CoverageEnding事件, 缺省30天
+    pub fn set_coverage_warn_days(&mut self, days: usize) {
+        self.coverage_warn_days = days;
+    }
+
+    /// 设置节假日及调休上班日, 参见day_kind()
+    ///
+    /// 只有get_buildin_calendar()缺省使用内置的节假日数据, 从外部csv文件或数据库加载的日历没有节假日数据,
+    /// 需要时由调用方设置, 如load_holidays_buildin()或HolidayTable::load_csv_file()
+    pub fn set_holiday_table(&mut self, table: Option<HolidayTable>) {
+        self.holiday_table = table;
+    }
+
+    pub fn holiday_table(&self) -> Option<&HolidayTable> {
+        self.holiday_table.as_ref()
+    }
+
+    /// 日期的类别, 可以区分"交易所休市但银行营业"的调休上班日与真正的节假日
+    ///
+    /// holiday_table中有date所在年份的数据时以其为准, 否则按日历数据推断: 周一至周五不交易的为节假日(名称为空),
+    /// 此时无法识别调休上班日
+    pub fn day_kind(&self, date: &MyDateType) -> DayKind {
+        if let Some(table) = &self.holiday_table
+            && table.contains_year(date_ymd(date).0)
+        {
+            return table.day_kind(date);
+        }
+        if is_weekend(date) {
+            return DayKind::Weekend;
+        }
+        let (_, index, _) = search_days(&self.full_day_list, date);
+        if index >= 0 && !self.full_day_list[index as usize].trading {
+            DayKind::Holiday {
+                name: String::new(),
+            }
+        } else {
+            DayKind::Weekday
+        }
+    }
+
+    /// date是节假日时返回名称, 参见day_kind()
+    pub fn holiday_at(&self, date: &MyDateType) -> Option<String> {
+        match self.day_kind(date) {
+            DayKind::Holiday { name } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// year年的节假日(仅周一至周五), holiday_table中没有该年数据时按日历数据推断, 名称为空
+    pub fn holidays_in(&self, year: i32) -> Vec<Holiday> {
+        if let Some(table) = &self.holiday_table
+            && table.contains_year(year)
+        {
+            return table.holidays_in(year);
+        }
+        self.full_day_list
+            .iter()
+            .filter(|day| date_ymd(&day.date).0 == year && !day.trading && !is_weekend(&day.date))
+            .map(|day| Holiday {
+                date: day.date,
+                name: String::new(),
+            })
+            .collect()
+    }
+
+    /// date所在的连续休息日(节假日及相连的周末), date是交易日或普通周末时返回None
+    ///
+    /// holiday_table中没有该年数据时按日历数据推断, 名称为空
+    pub fn current_holiday_block(&self, date: &MyDateType) -> Option<HolidayBlock> {
+        if let Some(table) = &self.holiday_table
+            && table.contains_year(date_ymd(date).0)
+        {
+            return table.holiday_block(date);
+        }
+        let list = &self.full_day_list;
+        let is_off = |date: &MyDateType| {
+            let (_, index, _) = search_days(list, date);
+            index >= 0 && !list[index as usize].trading
+        };
+        find_holiday_block(date, is_off, |date| {
+            (!is_weekend(date) && is_off(date)).then(String::new)
+        })
+    }
+
+    fn apply_time(
+        &mut self,
+        datetime: &MyDateTimeType,
+        fail_safe: bool,
+    ) -> Result<(TimeChange, Vec<CalendarEvent>)> {
         // println!("time_changed() called.");
         let curr_date = datetime.date();
         let old_date = self.current_time.date();
+        let mut events = Vec::new();
         if old_date != curr_date {
             log::trace!("自然日改变: {} => {}", old_date, curr_date);
+            events.push(CalendarEvent::NaturalDayChanged {
+                prev: old_date,
+                curr: curr_date,
+            });
+        }
+
+        let (current_tday, trading, mut error_msg) = self.state_at(datetime, fail_safe)?;
+        self.current_time = *datetime;
+        let old_trading = self.is_trading;
+        self.set_is_trading(trading);
+
+        let old_tday = self.curr_tday;
+        if old_tday != current_tday {
+            self.curr_tday = current_tday;
+            // get_prev_trading_day()的错误无需汇报，因为我们time_changed总是向前推进
+            self.prev_tday = match self.get_prev_trading_day(&current_tday, 1) {
+                Ok(pretday) => pretday.date,
+                Err(_) => prev_working_day(&current_tday, 1),
+            };
+            match self.get_next_trading_day(&current_tday, 1) {
+                Ok(day) => {
+                    self.next_tday = day.date;
+                }
+                Err(_) => {
+                    // 这个可能会发生在年末岁初,calendar没有及时更新的情况下
+                    if fail_safe {
+                        error_msg = Some(format!(
+                            "out of range ({:?} ~ {:?}), when get next for {}. 请更新交易日历",
+                            self.min_date(),
+                            self.max_date(),
+                            &current_tday
+                        ));
+                        self.next_tday = match self.estimated_tradingday(&current_tday) {
+                            Some(day) => {
+                                error_msg = error_msg
+                                    .map(|msg| format!("{msg}, estimated by predict_holidays()"));
+                                day.next
+                            }
+                            None => next_working_day(&current_tday, 1),
+                        };
+                    } else {
+                        return Err(anyhow!(
+                            "TradeCalendar::time_change(), out of range ({:?} ~ {:?}) for full_days_list",
+                            self.min_date(),
+                            self.max_date(),
+                        ));
+                    }
+                }
+            };
+            log::info!(
+                "交易日改变: {} => {}, prev {}, next {}, shift point {}",
+                old_tday,
+                self.curr_tday,
+                self.prev_tday,
+                self.next_tday,
+                self.cfg.tday_shift
+            );
+            events.push(CalendarEvent::TradingDayChanged {
+                prev: self.prev_tday,
+                curr: self.curr_tday,
+                next: self.next_tday,
+            });
+        }
+        if old_trading != trading {
+            events.push(match trading {
+                true => CalendarEvent::TradingStarted,
+                false => CalendarEvent::TradingStopped,
+            });
         }
+        if let Some(reason) = &error_msg {
+            events.push(CalendarEvent::FailSafeUsed {
+                reason: reason.clone(),
+            });
+        }
+        if old_date != curr_date {
+            // 日历数据中curr_date之后剩余的天数
+            let days_left = self.full_day_list.len()
+                - self.full_day_list.partition_point(|d| d.date <= curr_date);
+            if days_left <= self.coverage_warn_days {
+                events.push(CalendarEvent::CoverageEnding { days_left });
+            }
+        }
+        for listener in self.listeners.iter_mut() {
+            for event in events.iter() {
+                listener(event);
+            }
+        }
+        Ok((
+            (old_tday, current_tday, old_date, curr_date, error_msg),
+            events,
+        ))
+    }
 
+    /// 计算datetime时刻的(交易日, is_trading, Option<Error_Message>), 不改变内部状态
+    ///
+    /// fail_safe: 参见time_changed()
+    fn state_at(
+        &self,
+        datetime: &MyDateTimeType,
+        fail_safe: bool,
+    ) -> Result<(MyDateType, bool, Option<String>)> {
+        let curr_date = datetime.date();
         let mut error_msg: Option<String> = None;
 
         let calendar: Tradingday;
@@ -728,12 +1821,15 @@ impl TradeCalendar {
             }
             if fail_safe {
                 calendar = self.fail_safe_tradingday(&curr_date);
+                if self.is_estimated(&curr_date) {
+                    error_msg =
+                        error_msg.map(|msg| format!("{msg}, estimated by predict_holidays()"));
+                }
             } else {
                 return Err(anyhow!(error_msg.expect("no fail")));
             }
         }
 
-        self.current_time = datetime.clone();
         let time = datetime.time();
 
         // 如果交易日当天有夜盘，则self.cfg.tday_shift作为下一个TradingDay的开始
@@ -749,54 +1845,77 @@ impl TradeCalendar {
             // 非交易日
             calendar.next
         };
-        let trading = self.check_is_trading(&time, &calendar);
-        self.set_is_trading(trading);
+        let trading = self.check_is_trading(&time, &calendar, self.session.as_ref());
+        Ok((current_tday, trading, error_msg))
+    }
 
-        let old_tday = self.curr_tday;
-        if old_tday != current_tday {
-            self.curr_tday = current_tday;
-            // get_prev_trading_day()的错误无需汇报，因为我们time_changed总是向前推进
-            self.prev_tday = match self.get_prev_trading_day(&current_tday, 1) {
-                Ok(pretday) => pretday.date,
-                Err(_) => prev_working_day(&current_tday, 1),
-            };
-            match self.get_next_trading_day(&current_tday, 1) {
-                Ok(day) => {
-                    self.next_tday = day.date;
+    /// after之后的下一个状态切换时刻, 及切换的类型, 不改变内部状态
+    ///
+    /// 状态只可能在tday_shift、连接时段(或交易时段)边界及0点发生切换, 所以最迟是下一个0点;
+    /// 连接时段的结束时间点本身仍属于连接时段, 所以切换时刻为其后1秒.
+    /// 长时间运行的服务可以据此精确休眠, 到时调用time_changed(), 无需轮询
+    ///
+    /// fail_safe: 参见time_changed(), 应与随后调用time_changed()时相同; 为false时超出日历范围报错
+    pub fn next_transition(&self, after: &MyDateTimeType, fail_safe: bool) -> Result<Transition> {
+        let date = after.date();
+        let mut candidates: Vec<MyDateTimeType> = vec![date_at_time(&date, &self.cfg.tday_shift)];
+        match &self.session {
+            Some(session) => {
+                for interval in session.intervals.iter() {
+                    candidates.push(date_at_time(&date, &interval.begin));
+                    candidates.push(date_at_time(&date, &interval.end));
                 }
-                Err(_) => {
-                    // 这个可能会发生在年末岁初,calendar没有及时更新的情况下
-                    if fail_safe {
-                        error_msg = Some(format!(
-                            "out of range ({:?} ~ {:?}), when get next for {}. 请更新交易日历",
-                            self.min_date(),
-                            self.max_date(),
-                            &current_tday
-                        ));
-                        self.next_tday = next_working_day(&current_tday, 1);
-                    } else {
-                        return Err(anyhow!(
-                            "TradeCalendar::time_change(), out of range ({:?} ~ {:?}) for full_days_list",
-                            self.min_date(),
-                            self.max_date(),
-                        ));
-                    }
+            }
+            None => {
+                candidates.push(date_at_time(&date, &self.cfg.night_begin));
+                candidates.push(date_at_time(&date, &self.cfg.day_begin));
+                for end in [&self.cfg.night_end, &self.cfg.day_end] {
+                    candidates.push(datetime_add_secs(&date_at_time(&date, end), 1));
                 }
-            };
-            log::info!(
-                "交易日改变: {} => {}, prev {}, next {}, shift point {}",
-                old_tday,
-                self.curr_tday,
-                self.prev_tday,
-                self.next_tday,
-                self.cfg.tday_shift
-            );
+            }
         }
-        Ok((old_tday, current_tday, old_date, curr_date, error_msg))
+        // 0点总是自然日切换
+        let next_midnight = date_at_hms(&tomorrow(&date), 0, 0, 0);
+        candidates.retain(|c| c > after && *c < next_midnight);
+        candidates.sort();
+        candidates.push(next_midnight);
+
+        let (old_tday, old_trading, _) = self.state_at(after, fail_safe)?;
+        for at in candidates {
+            let (tday, trading, _) = self.state_at(&at, fail_safe)?;
+            let mut kinds = Vec::new();
+            if at == next_midnight {
+                kinds.push(TransitionKind::NaturalDayChanged);
+            }
+            if tday != old_tday {
+                kinds.push(TransitionKind::TradingDayChanged);
+            }
+            if trading != old_trading {
+                kinds.push(if trading {
+                    TransitionKind::TradingStarted
+                } else {
+                    TransitionKind::TradingStopped
+                });
+            }
+            if !kinds.is_empty() {
+                return Ok(Transition { at, kinds });
+            }
+        }
+        unreachable!("natural day always changes at midnight")
     }
 
     /// 重算is_trading变量, 当前Tradingday已知
-    fn check_is_trading(&self, time: &MyTimeType, tday: &Tradingday) -> bool {
+    ///
+    /// session为None时使用cfg配置的连接时段, 否则按交易时段判断
+    fn check_is_trading(
+        &self,
+        time: &MyTimeType,
+        tday: &Tradingday,
+        session: Option<&SessionTemplate>,
+    ) -> bool {
+        if let Some(session) = session {
+            return tday.is_in_session(time, session);
+        }
         if time >= &self.cfg.night_begin {
             return tday.night;
         }
@@ -816,8 +1935,35 @@ impl TradeCalendar {
         }
     }
 
+    /// date超出日历数据范围(max_date之后)时, 返回按predict_holidays()估算的结果, 否则返回None
+    fn estimated_tradingday(&self, date: &MyDateType) -> Option<Tradingday> {
+        if self.max_date().is_none_or(|max_date| date <= max_date) {
+            return None;
+        }
+        let year = date_ymd(date).0;
+        let days = self
+            .estimated_years
+            .lock()
+            .expect("poisoned")
+            .entry(year)
+            .or_insert_with(|| estimate_tradingdays(year).ok().map(Arc::new))
+            .clone()?;
+        let (_, index, _) = search_days(&days, date);
+        (index >= 0).then(|| days[index as usize].clone())
+    }
+
+    /// date的交易日信息是否为估算的, 即time_changed(fail_safe=true)在max_date之后使用predict_holidays()的估算结果
+    pub fn is_estimated(&self, date: &MyDateType) -> bool {
+        self.estimated_tradingday(date).is_some()
+    }
+
     /// 已经超出了full_day_list的范围, 只能按照working day的方式, 构造一个范围外的Tradingday
-    pub(crate) fn fail_safe_tradingday(&mut self, input: &MyDateType) -> Tradingday {
+    pub(crate) fn fail_safe_tradingday(&self, input: &MyDateType) -> Tradingday {
+        // 超出日历范围时, 优先使用按农历及放假办法估算的日历
+        if let Some(day) = self.estimated_tradingday(input) {
+            return day;
+        }
+
         // 需要构造出一个Tradingday对象出来
         let weekday = input.weekday();
         let mut calendar = Tradingday::new_dummy(&input);
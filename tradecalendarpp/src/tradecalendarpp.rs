@@ -4,7 +4,7 @@ use tradecalendar::jcswitch::{
     date_from_days_since_epoch, date_to_days_since_epoch, datetime_from_timestamp_nanos, make_date,
     time_from_midnight_nanos, time_to_midnight_nanos,
 };
-use tradecalendar::{self, NotTradingSearchMethod, TradingdayCache};
+use tradecalendar::{self, NotTradingSearchMethod, SessionTemplate, TradingdayCache};
 
 pub struct TradeCalendarPP {
     entity: tradecalendar::TradeCalendar,
@@ -111,7 +111,11 @@ impl TradeCalendarPP {
             NotTradingSearchMethod::Prev
         };
         self.entity
-            .trading_day_from_datetime(&datetime, method, is_finance_item)
+            .trading_day_from_datetime(
+                &datetime,
+                method,
+                &SessionTemplate::from_finance_flag(is_finance_item),
+            )
             .and_then(|r| Ok(date_to_days_since_epoch(&r)))
     }
 
@@ -7,7 +7,8 @@ use pyo3_stub_gen::define_stub_info_gatherer;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
 
 use tradecalendar::{
-    self, NotTradingSearchMethod, TradingdayCache, jcswitch::make_date, reload_calendar,
+    self, NotTradingSearchMethod, SessionTemplate, TradingdayCache, jcswitch::make_date,
+    reload_calendar,
 };
 
 fn to_pyerr(e: anyhow::Error) -> PyErr {
@@ -159,7 +160,11 @@ impl TradeCalendar {
             NotTradingSearchMethod::Prev
         };
         self.entity
-            .trading_day_from_datetime(&input, method, is_finance_item)
+            .trading_day_from_datetime(
+                &input,
+                method,
+                &SessionTemplate::from_finance_flag(is_finance_item),
+            )
             .map_err(to_pyerr)
     }
 